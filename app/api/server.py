@@ -9,7 +9,7 @@ from pydantic import BaseModel
 from sqlmodel import select
 
 from app.db.session import init_db, get_session
-from app.db.models import Item
+from app.db.models import IndexTombstone, Item
 from app.search.semantic_search import (
     semantic_search,
     search_images_only,
@@ -239,8 +239,9 @@ async def delete_item(item_id: int):
     """
     Delete an item by ID
 
-    Note: This only deletes from the database.
-    FAISS index cleanup requires rebuild.
+    Note: Only the item row goes here, the desktop app's delete_item also
+    cleans up tags, pins and blobs. Its vectors are dropped from FAISS the next
+    time an index is loaded or saved (see DualVectorStore.purge_deleted)
     """
     with get_session() as session:
         statement = select(Item).where(Item.id == item_id)
@@ -250,6 +251,7 @@ async def delete_item(item_id: int):
             raise HTTPException(status_code=404, detail=f"Item {item_id} not found")
 
         session.delete(item)
+        session.merge(IndexTombstone(item_id=item_id))
         session.commit()
 
         return {"status": "deleted", "id": item_id}
@@ -1,7 +1,34 @@
+import os
+
+try:
+    import tomllib
+except ImportError:  # Python < 3.11
+    tomllib = None
+
 db_file = "clipmind_history.json"
 embedded_model_name = "all-MiniLM-L6-v2"
 top_k_results = 5
 sqlite_url = "sqlite:///clipmind.db"
 faiss_index_path = "faiss/clipmind.index"
 faiss_idmap_path = "faiss/idmap.npy"
-PROCESSED_CACHE_FILE = "processed_screenshots.json"
\ No newline at end of file
+# Old processed-file list, only read once to move it into screenshot_ledger
+PROCESSED_CACHE_FILE = "processed_screenshots.json"
+
+
+def _load_settings():
+    """[search] from the desktop app's settings.toml, the Tauri side passes its path
+    in CLIPMIND_SETTINGS. Missing or broken files keep the defaults above"""
+    path = os.environ.get("CLIPMIND_SETTINGS")
+    if not path or tomllib is None:
+        return {}
+    try:
+        with open(path, "rb") as f:
+            return tomllib.load(f).get("search", {})
+    except (OSError, tomllib.TOMLDecodeError) as e:
+        print(f"[CONFIG] Ignoring {path}: {e}")
+        return {}
+
+
+_search = _load_settings()
+top_k_results = int(_search.get("top_k_results", top_k_results))
+embedded_model_name = _search.get("embedding_model", embedded_model_name)
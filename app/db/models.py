@@ -9,6 +9,9 @@ class Item(SQLModel, table=True):
     __table_args__ = (
         Index('ix_item_content_hash', 'content_hash'),  # Index for fast duplicate lookups
         Index('ix_item_source', 'source'),  # Index for filtering by source type
+        # Ids are never reused, FAISS and index_tombstone know items only by id.
+        # The desktop app rebuilds older tables this way (see storage.rs)
+        {'sqlite_autoincrement': True},
     )
 
     id: Optional[int] = Field(default=None, primary_key=True)
@@ -25,3 +28,12 @@ class Item(SQLModel, table=True):
     readable_time: str = Field(
         default_factory=lambda: datetime.now().strftime("%Y-%m-%d %H:%M:%S")
     )
+
+
+class IndexTombstone(SQLModel, table=True):
+    """Items deleted since their vectors went into FAISS, dropped by DualVectorStore.purge_deleted"""
+
+    __tablename__ = "index_tombstone"
+
+    item_id: int = Field(primary_key=True)
+    deleted_ts: int = Field(default_factory=lambda: int(datetime.now().timestamp()))
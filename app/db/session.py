@@ -1,4 +1,5 @@
 import os
+import sys
 from sqlmodel import SQLModel, create_engine, Session
 from app.core import config
 
@@ -8,9 +9,39 @@ def ensure_folders_exist():
     if faiss_dir and not os.path.exists(faiss_dir):
         os.makedirs(faiss_dir, exist_ok=True)
 
+def read_db_key():
+    """Raw SQLCipher key of an encrypted history. The desktop app writes it to our stdin
+    after an unlock instead of putting it in the environment, where other processes could read it"""
+    if os.environ.get("CLIPMIND_DB_KEY_STDIN") != "1":
+        return None
+    key = sys.stdin.readline().strip()
+    if not key:
+        sys.exit("[ERROR] CLIPMIND_DB_KEY_STDIN is set but no key was written to stdin")
+    return key
+
 def build_engine():
     #create the SQLAlchemy to work with SQLite
-    return create_engine(config.sqlite_url, echo=False)
+    key = read_db_key()
+    if not key:
+        return create_engine(config.sqlite_url, echo=False)
+
+    # Encrypted history (SQLCipher)
+    try:
+        import sqlcipher3
+    except ImportError:
+        sys.exit(
+            "[ERROR] The ClipMind history is encrypted but sqlcipher3 isn't installed. "
+            "Install it with: pip install sqlcipher3-binary"
+        )
+
+    db_path = config.sqlite_url.removeprefix("sqlite:///")
+
+    def connect():
+        conn = sqlcipher3.connect(db_path, check_same_thread=False)
+        conn.execute(f"PRAGMA key = \"x'{key}'\"")
+        return conn
+
+    return create_engine("sqlite://", creator=connect, echo=False)
 
 engine = build_engine()
 
@@ -7,7 +7,7 @@ from typing import List
 from sqlmodel import select
 
 from app.db.session import init_db, get_session
-from app.db.models import Item
+from app.db.models import IndexTombstone, Item
 from app.index.vector_store import DualVectorStore
 from app.search.encoder import encode_many_texts, VECTOR_DIM
 from app.search.clip_encoder import encode_image, IMAGE_VECTOR_DIM
@@ -99,6 +99,12 @@ def rebuild_index(batchsize: int = 1024):
 
     # Save indexes
     store.save()
+
+    # The fresh index only has live items, every tombstone is dealt with
+    with get_session() as session:
+        for tombstone in session.exec(select(IndexTombstone)).all():
+            session.delete(tombstone)
+        session.commit()
     stats = store.get_stats()
     print("[SYSTEM] Index rebuilt and saved successfully.")
     print(f"[STATS] Text vectors: {stats['text_vectors']}, Image vectors: {stats['image_vectors']}")
@@ -9,7 +9,11 @@ import os
 from typing import List, Tuple, Literal
 import numpy as np
 import faiss
+from sqlalchemy.exc import OperationalError
+from sqlmodel import select, col
 from app.core import config
+from app.db.models import IndexTombstone, Item
+from app.db.session import get_session
 
 VectorKind = Literal["text", "image"]
 
@@ -59,6 +63,40 @@ class DualVectorStore:
             self.image_index = faiss.IndexFlatL2(self.image_dim)
             self.image_id_map = []
 
+        self.purge_deleted()
+
+    @staticmethod
+    def _remove(index, id_map: List[int], deleted: set) -> List[int]:
+        """Drop every vector of a deleted item, returns the new id map"""
+        positions = [pos for pos, item_id in enumerate(id_map) if item_id in deleted]
+        if positions:
+            # IndexFlat shifts the remaining vectors down, same order as the id map
+            index.remove_ids(np.array(positions, dtype=np.int64))
+        return [item_id for item_id in id_map if item_id not in deleted]
+
+    def purge_deleted(self) -> int:
+        """
+        Drop vectors of items deleted since they were indexed (retention, delete_item)
+        Returns how many vectors went
+        """
+        try:
+            with get_session() as session:
+                # item ids are AUTOINCREMENT so a tombstoned id stays dead, the check only
+                # matters for a database the desktop app hasn't migrated yet
+                statement = select(IndexTombstone.item_id).where(col(IndexTombstone.item_id).not_in(select(Item.id)))
+                deleted = set(session.exec(statement).all())
+        except OperationalError:
+            # No tombstone table yet, nothing was deleted
+            return 0
+
+        if not deleted:
+            return 0
+
+        before = self.text_index.ntotal + self.image_index.ntotal
+        self.text_id_map = self._remove(self.text_index, self.text_id_map, deleted)
+        self.image_id_map = self._remove(self.image_index, self.image_id_map, deleted)
+        return before - self.text_index.ntotal - self.image_index.ntotal
+
     def add_text_vector(self, item_id: int, vector: np.ndarray):
         """Add a text vector (from clipboard or OCR)"""
 
@@ -125,6 +163,8 @@ class DualVectorStore:
         Writes FAISS index and id_map to disk
         CALL AFTER BATCHES OF ADDS
         """
+        # Items can be deleted while a watcher holds the index in memory
+        self.purge_deleted()
 
         #save the text index
         faiss.write_index(self.text_index, self.text_index_path)
@@ -0,0 +1,147 @@
+# Purpose: OCR + embeddings for screenshots the Tauri app ingested
+# The app is the only thing that inserts screenshots (pHash near-dup gate, every watched root),
+# it stores them as "[OCR pending]" with a row in ocr_job. This worker takes jobs from there,
+# fills in the OCR text, adds the text + CLIP vectors and deletes the job. It never inserts items
+# Used instead of screenshot_watcher.py when the app runs the backend
+
+import time
+import os
+from sqlalchemy import text as sql_text
+from sqlalchemy.exc import OperationalError
+from app.db.session import init_db, get_session
+from app.db.models import Item
+from app.index.vector_store import DualVectorStore
+from app.search.encoder import encode_text_to_vector, VECTOR_DIM
+from app.search.clip_encoder import encode_image, IMAGE_VECTOR_DIM
+from app.ingest.screenshot_watcher import extract_text_from_image
+
+# Same table the app creates in clipmind-ui/src-tauri/src/fingerprint.rs
+JOB_SCHEMA = """
+CREATE TABLE IF NOT EXISTS ocr_job (
+    item_id INTEGER NOT NULL PRIMARY KEY,
+    path VARCHAR NOT NULL,
+    queued_ts INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error VARCHAR
+)
+"""
+
+# A job that failed this often stays in the table with its error, and in the ledger as "failed"
+MAX_ATTEMPTS = 3
+
+BATCH_SIZE = 20
+
+
+def next_jobs(limit: int = BATCH_SIZE) -> list:
+    """Oldest jobs that still have attempts left"""
+    with get_session() as session:
+        rows = session.exec(
+            sql_text(
+                "SELECT item_id FROM ocr_job WHERE attempts < :max ORDER BY queued_ts, item_id LIMIT :limit"
+            ).bindparams(max=MAX_ATTEMPTS, limit=limit)
+        ).all()
+    return [row[0] for row in rows]
+
+
+def finish(item_id: int):
+    """Job done: drop it and mark the file indexed in the ledger"""
+    with get_session() as session:
+        session.exec(sql_text("DELETE FROM ocr_job WHERE item_id = :id").bindparams(id=item_id))
+        try:
+            session.exec(
+                sql_text(
+                    "UPDATE screenshot_ledger SET status = 'indexed', error = NULL, updated_ts = :now WHERE item_id = :id"
+                ).bindparams(id=item_id, now=int(time.time()))
+            )
+        except OperationalError:
+            # No ledger yet, nothing to mark
+            pass
+        session.commit()
+
+
+def fail(item_id: int, error: str):
+    """Count a failed attempt, the last one also shows up in the app's failed screenshot list"""
+    with get_session() as session:
+        session.exec(
+            sql_text("UPDATE ocr_job SET attempts = attempts + 1, error = :error WHERE item_id = :id").bindparams(
+                id=item_id, error=error
+            )
+        )
+        session.exec(
+            sql_text(
+                "UPDATE screenshot_ledger SET status = 'failed', error = :error, updated_ts = :now "
+                "WHERE item_id = :id AND (SELECT attempts FROM ocr_job WHERE item_id = :id) >= :max"
+            ).bindparams(id=item_id, error=error, now=int(time.time()), max=MAX_ATTEMPTS)
+        )
+        session.commit()
+
+
+def process(item_id: int, store: DualVectorStore):
+    with get_session() as session:
+        item = session.get(Item, item_id)
+    if item is None:
+        # Deleted since it was queued
+        finish(item_id)
+        return
+
+    # Read from the item, the ledger reconcile updates blob_uri when a file moves
+    path = item.blob_uri
+    if not path or not os.path.exists(path):
+        fail(item_id, f"File not found: {path}")
+        return
+
+    text = extract_text_from_image(path)
+
+    # Work out both vectors before touching the index, so a retry never adds them twice
+    try:
+        text_vector = encode_text_to_vector(text) if text != "[No text detected]" else None
+        image_vector = encode_image(path)
+    except Exception as e:
+        print(f"[ERROR] Failed to embed item #{item_id}: {e}")
+        fail(item_id, str(e))
+        return
+
+    with get_session() as session:
+        item = session.get(Item, item_id)
+        if item is None:
+            finish(item_id)
+            return
+        item.text = text
+        session.add(item)
+        session.commit()
+
+    if text_vector is not None:
+        store.add_text_vector(item_id=item_id, vector=text_vector)
+    store.add_image_vector(item_id=item_id, vector=image_vector)
+    store.save()
+    finish(item_id)
+
+    shortened_text = text[:80] + "..." if len(text) > 80 else text
+    print("[ITEM] OCR done for Item #" + str(item_id) + " [SCREENSHOT] | " + repr(shortened_text))
+
+
+def run(poll_seconds: int = 2):
+    init_db()
+    with get_session() as session:
+        session.exec(sql_text(JOB_SCHEMA))
+        session.commit()
+    store = DualVectorStore(text_dim=VECTOR_DIM, image_dim=IMAGE_VECTOR_DIM)
+
+    print("[SYSTEM] OCR worker started, waiting for screenshots from the app")
+    try:
+        while True:
+            jobs = next_jobs()
+            for item_id in jobs:
+                try:
+                    process(item_id, store)
+                except Exception as e:
+                    print(f"[ERROR] OCR job for item #{item_id} failed: {e}")
+                    fail(item_id, str(e))
+            if not jobs:
+                time.sleep(poll_seconds)
+    except KeyboardInterrupt:
+        print("[SYSTEM] OCR worker stopped.")
+
+
+if __name__ == "__main__":
+    run()
@@ -2,16 +2,21 @@
 # stores path to original screenshot + dual embeddings
 # does not store the image again, references the existing files
 # added persistent tracking! (remembers which files are processed across restarts)
+# tracking now lives in the screenshot_ledger table, shared with the Tauri app
 # FIXED: Auto-detects OneDrive Pictures folder
+# Standalone only (run_watchers.py). When the desktop app runs, it ingests screenshots itself
+# and app/ingest/ocr_worker.py does the OCR, this watcher isn't started
 
 import time
 import os
 from pathlib import Path
-from typing import Optional, Set
+from typing import Optional
 import xxhash
 from PIL import Image
 import json
 from sqlmodel import select
+from sqlalchemy import text as sql_text
+from sqlalchemy.exc import OperationalError
 from app.db.session import init_db, get_session
 from app.db.models import Item
 from app.index.vector_store import DualVectorStore
@@ -42,6 +47,9 @@ DEFAULT_SCREENSHOT_FOLDER = get_screenshot_folder()
 # Supported image extensions
 IMAGE_EXTENSIONS = {'.png', '.jpg', '.jpeg', '.bmp', '.tiff', '.webp'}
 
+# Text the Tauri app stores for screenshots it ingested, OCR is left to us
+OCR_PENDING = "[OCR pending]"
+
 # === OCR EXTRACTION ===
 
 # Initialize EasyOCR reader (lazy loading)
@@ -96,16 +104,24 @@ def compute_hash(text: str) -> str:
     return xxhash.xxh64(text.encode('utf-8')).hexdigest()
 
 
-def check_exact_duplicate(text_hash: str) -> Optional[int]:
-    """Check if exact duplicate exists in database"""
+def find_item_by_hash(content_hash: str) -> Optional[Item]:
+    """Find an existing item with the same content hash"""
     with get_session() as session:
-        statement = select(Item).where(Item.content_hash == text_hash).limit(1)
-        item = session.exec(statement).first()
+        statement = select(Item).where(Item.content_hash == content_hash).limit(1)
+        return session.exec(statement).first()
 
-        if item:
-            return item.id
 
-    return None
+def is_rejected_capture(content_hash: str) -> bool:
+    """Check if the Tauri app rejected this screenshot as a near-duplicate (capture_reject table)"""
+    try:
+        with get_session() as session:
+            row = session.exec(
+                sql_text("SELECT 1 FROM capture_reject WHERE content_hash = :hash LIMIT 1").bindparams(hash=content_hash)
+            ).first()
+            return row is not None
+    except OperationalError:
+        # Table only exists once the app has run
+        return False
 
 
 def is_junk_text(text: str) -> bool:
@@ -129,56 +145,97 @@ def compute_image_hash(file_path: str) -> str:
 
 # === FILE TRACKING ===
 
+# Same table the Tauri app keeps in clipmind-ui/src-tauri/src/ledger.rs
+LEDGER_SCHEMA = """
+CREATE TABLE IF NOT EXISTS screenshot_ledger (
+    path VARCHAR NOT NULL PRIMARY KEY,
+    size INTEGER NOT NULL,
+    mtime INTEGER NOT NULL,
+    content_hash VARCHAR,
+    item_id INTEGER,
+    status VARCHAR NOT NULL,
+    error VARCHAR,
+    updated_ts INTEGER NOT NULL
+)
+"""
+
+# Statuses that mean there's nothing left to do for a file
+# ("ingested" means the app saved it but OCR is still ours to do)
+DONE_STATUSES = {"indexed", "duplicate", "rejected"}
+
+
 class ProcessedFilesTracker:
     """
     Track which files we've already processed
-    Saved to disk so we remember across restarts
+    Kept in the screenshot_ledger table so it survives restarts and is shared with the app
     """
 
-    def __init__(self, cache_file: str = PROCESSED_CACHE_FILE):
-        self.cache_file = cache_file
-        self.processed: Set[str] = self._load_cache()
-        self.save_counter = 0
-        self.save_interval = 1  # Save cache every N new files
-
-    def _load_cache(self) -> Set[str]:
-        """Load processed files list from disk"""
-        if os.path.exists(self.cache_file):
-            try:
-                with open(self.cache_file, 'r') as f:
-                    data = json.load(f)
-                    print(f"[CACHE] Loaded {len(data)} previously processed files")
-                    return set(data)
-            except Exception as e:
-                print(f"[WARN] Could not load cache: {e}")
-                return set()
-        return set()
-
-    def _save_cache(self):
-        """Save processed files list to disk"""
+    def __init__(self, legacy_cache_file: str = PROCESSED_CACHE_FILE):
+        with get_session() as session:
+            session.exec(sql_text(LEDGER_SCHEMA))
+            session.commit()
+        self._import_legacy_cache(legacy_cache_file)
+
+    def _import_legacy_cache(self, cache_file: str):
+        """One-time move of processed_screenshots.json into the ledger"""
+        if not os.path.exists(cache_file):
+            return
         try:
-            with open(self.cache_file, 'w') as f:
-                json.dump(list(self.processed), f)
+            with open(cache_file, 'r') as f:
+                paths = json.load(f)
         except Exception as e:
-            print(f"[ERROR] Could not save cache: {e}")
+            print(f"[WARN] Could not load legacy cache: {e}")
+            return
 
-    def is_processed(self, file_path: str) -> bool:
-        """Check if file has been processed"""
-        return file_path in self.processed
+        imported = 0
+        for file_path in paths:
+            # Files that are gone would only be pruned again later
+            if os.path.exists(file_path) and not self.is_processed(file_path):
+                self.mark_processed(file_path)
+                imported += 1
 
-    def mark_processed(self, file_path: str):
-        """Mark file as processed and periodically save to disk"""
-        self.processed.add(file_path)
-        self.save_counter += 1
+        os.replace(cache_file, cache_file + ".migrated")
+        print(f"[CACHE] Moved {imported} previously processed files into the ledger")
 
-        # Save cache periodically
-        if self.save_counter >= self.save_interval:
-            self._save_cache()
-            self.save_counter = 0
+    def is_processed(self, file_path: str) -> bool:
+        """Check if file has been processed"""
+        with get_session() as session:
+            row = session.exec(
+                sql_text("SELECT status FROM screenshot_ledger WHERE path = :path").bindparams(path=file_path)
+            ).first()
+        return row is not None and row[0] in DONE_STATUSES
 
-    def force_save(self):
-        """Force save cache to disk (call on shutdown)"""
-        self._save_cache()
+    def _record(self, file_path: str, status: str, content_hash: Optional[str], item_id: Optional[int], error: Optional[str]):
+        try:
+            stat = os.stat(file_path)
+            size, mtime = stat.st_size, int(stat.st_mtime)
+        except OSError:
+            size, mtime = 0, 0
+
+        with get_session() as session:
+            session.exec(
+                sql_text(
+                    "INSERT INTO screenshot_ledger (path, size, mtime, content_hash, item_id, status, error, updated_ts) "
+                    "VALUES (:path, :size, :mtime, :hash, :item_id, :status, :error, :now) "
+                    "ON CONFLICT(path) DO UPDATE SET size = excluded.size, mtime = excluded.mtime, "
+                    "content_hash = COALESCE(excluded.content_hash, content_hash), "
+                    "item_id = COALESCE(excluded.item_id, item_id), "
+                    "status = excluded.status, error = excluded.error, updated_ts = excluded.updated_ts"
+                ).bindparams(
+                    path=file_path, size=size, mtime=mtime, hash=content_hash, item_id=item_id,
+                    status=status, error=error, now=int(time.time()),
+                )
+            )
+            session.commit()
+
+    def mark_processed(self, file_path: str, content_hash: Optional[str] = None, item_id: Optional[int] = None,
+                       status: str = "indexed"):
+        """Mark file as processed"""
+        self._record(file_path, status, content_hash, item_id, None)
+
+    def mark_failed(self, file_path: str, error: str, content_hash: Optional[str] = None, item_id: Optional[int] = None):
+        """Record why a file couldn't be processed, it's retried on the next scan"""
+        self._record(file_path, "failed", content_hash, item_id, error)
 
 
 # === MAIN WATCHER ===
@@ -219,7 +276,6 @@ def watch_screenshots(folder_path: str = None, poll_seconds: int = 5):
 
 
 
-    print(f"[SYSTEM] Tracking {len(tracker.processed)} total files")
     print("[SYSTEM] Screenshot watcher started! Take screenshots to capture them.")
     print("=" * 60)
 
@@ -253,6 +309,23 @@ def watch_screenshots(folder_path: str = None, poll_seconds: int = 5):
                 total_screenshots += 1
                 print(f"[NEW] Screenshot detected: {file_path.name}")
 
+                # Check for duplicates before OCR, no point reading text we won't keep
+                content_hash = compute_image_hash(file_str)
+
+                if is_rejected_capture(content_hash):
+                    duplicates_skipped += 1
+                    print(f"[SKIP] Near-duplicate screenshot rejected by the app (total dupes: {duplicates_skipped})")
+                    tracker.mark_processed(file_str, content_hash, status="rejected")
+                    continue
+
+                existing = find_item_by_hash(content_hash)
+
+                if existing is not None and existing.text != OCR_PENDING:
+                    duplicates_skipped += 1
+                    print(f"[SKIP] Duplicate text of Item #{existing.id} (total dupes: {duplicates_skipped})")
+                    tracker.mark_processed(file_str, content_hash, existing.id, status="duplicate")
+                    continue
+
                 # Extract text via OCR (optional)
                 text = extract_text_from_image(file_str)
 
@@ -264,23 +337,19 @@ def watch_screenshots(folder_path: str = None, poll_seconds: int = 5):
                 #     print(f"[SKIP] Junk text")
                 #     continue
 
-                # Check for exact duplicates
-                content_hash = compute_image_hash(file_str)
-                existing_id = check_exact_duplicate(content_hash)
-
-                if existing_id is not None:
-                    duplicates_skipped += 1
-                    print(f"[SKIP] Duplicate text of Item #{existing_id} (total dupes: {duplicates_skipped})")
-                    continue
-
                 # Save to database with screenshot data
                 with get_session() as session:
-                    new_item = Item(
-                        text=text,
-                        content_hash=content_hash,
-                        source="screenshot",
-                        blob_uri=file_str  # Store path to original screenshot
-                    )
+                    if existing is not None:
+                        # Already inserted by the app, just fill in the OCR text
+                        new_item = session.get(Item, existing.id)
+                        new_item.text = text
+                    else:
+                        new_item = Item(
+                            text=text,
+                            content_hash=content_hash,
+                            source="screenshot",
+                            blob_uri=file_str  # Store path to original screenshot
+                        )
                     session.add(new_item)
                     session.commit()
                     session.refresh(new_item)
@@ -303,9 +372,10 @@ def watch_screenshots(folder_path: str = None, poll_seconds: int = 5):
                     stats = store.get_stats()
                     print(
                         f"[SYSTEM] Indexes saved - Text: {stats['text_vectors']}, Image: {stats['image_vectors']}")
-                    tracker.mark_processed(file_str)
+                    tracker.mark_processed(file_str, content_hash, new_item.id)
                 except Exception as e:
                     print(f"[ERROR] Failed to index: {e}")
+                    tracker.mark_failed(file_str, str(e), content_hash, new_item.id)
 
                 # Display confirmation
                 if len(text) > 80:
@@ -325,8 +395,7 @@ def watch_screenshots(folder_path: str = None, poll_seconds: int = 5):
 
     except KeyboardInterrupt:
         print("[SYSTEM] Shutting down...")
-        tracker.force_save()
-        print("[SYSTEM] Cache saved. Screenshot watcher stopped.")
+        print("[SYSTEM] Screenshot watcher stopped.")
 
 
 def main():
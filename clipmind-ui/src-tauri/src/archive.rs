@@ -0,0 +1,736 @@
+// Export and import of history.
+// JSON and NDJSON carry every item with its tags, collections and pin, Markdown is
+// for reading and export-only. A zip bundle is history.json plus the screenshot
+// files under blobs/, so it still works on a machine where those paths don't exist.
+//
+// Import is dedup-aware through content_hash: an item that's already in the history
+// isn't inserted again, it only picks up the imported tags and collections. Text goes
+// through the same secret scan as a live copy, so it's skipped, redacted or expired
+// the way clipboard_watcher.rs would have done it.
+// Besides our own files it reads a bare array of items and the old
+// clipmind_history.json, missing fields get sensible defaults.
+// Both directions report progress on PROGRESS_EVENT
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::clipboard_watcher::compute_hash;
+use crate::image_sandbox::ImageSandbox;
+use crate::sensitive::{self, Action, Detector};
+use crate::storage::{self, now_ts, Item, NewItem, Store, ITEM_COLUMNS};
+use crate::tags::{self, Filter};
+use crate::{fingerprint, pins, settings, vault};
+
+pub const PROGRESS_EVENT: &str = "history://progress";
+
+const VERSION: u32 = 1;
+
+// Name of the item list inside a zip bundle
+const BUNDLE_INDEX: &str = "history.json";
+const BUNDLE_BLOBS: &str = "blobs/";
+
+// Don't flood the webview, one event per this many items (and one at the end)
+const PROGRESS_EVERY: usize = 100;
+
+const SOURCES: &[&str] = &["clipboard", "screenshot"];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    Json,
+    Ndjson,
+    Markdown,
+    Zip,
+}
+
+impl Format {
+    /// What an import file is, from its extension
+    fn detect(path: &Path) -> Result<Format, String> {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "json" => Ok(Format::Json),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            "zip" => Ok(Format::Zip),
+            "md" | "markdown" => Err("Markdown exports can't be imported, use JSON, NDJSON or a zip bundle".to_string()),
+            _ => Err(format!("Don't know how to import a .{} file", ext)),
+        }
+    }
+}
+
+/// Which items to export, everything when left empty
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportFilter {
+    pub source: Option<String>,
+    /// Unix seconds, inclusive
+    pub after: Option<i64>,
+    /// Unix seconds, exclusive
+    pub before: Option<i64>,
+    /// Items need every one of these
+    pub tags: Vec<String>,
+    pub collection: Option<String>,
+    pub ids: Option<Vec<i64>>,
+}
+
+#[derive(Serialize)]
+struct Record {
+    #[serde(flatten)]
+    item: Item,
+    tags: Vec<String>,
+    collections: Vec<String>,
+    pinned: bool,
+    /// Path of the file inside a zip bundle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blob: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u32,
+    exported_ts: i64,
+    items: &'a [Record],
+}
+
+/// An item as read back, lenient about what's there so older exports work too
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Incoming {
+    #[serde(alias = "content")]
+    text: String,
+    content_hash: Option<String>,
+    source: Option<String>,
+    blob_uri: Option<String>,
+    #[serde(alias = "timestamp", alias = "ts")]
+    created_ts: Option<Value>,
+    tags: Vec<String>,
+    collections: Vec<String>,
+    pinned: bool,
+    blob: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Progress {
+    pub operation: &'static str,
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize)]
+pub struct ExportReport {
+    pub path: String,
+    pub format: Format,
+    pub items: usize,
+    /// Files put in a zip bundle
+    pub blobs: usize,
+    /// Items whose file couldn't be read, exported without it
+    pub missing_blobs: usize,
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub format: Format,
+    pub total: usize,
+    pub imported: usize,
+    /// Already in the history, only got the tags and collections merged in
+    pub duplicates: usize,
+    /// Not an item, or one that couldn't be saved
+    pub skipped: usize,
+    /// Files copied out of a zip bundle
+    pub blobs: usize,
+}
+
+fn progress(app: &AppHandle, operation: &'static str, done: usize, total: usize) {
+    if !done.is_multiple_of(PROGRESS_EVERY) && done != total {
+        return;
+    }
+    if let Err(e) = app.emit(PROGRESS_EVENT, Progress { operation, done, total }) {
+        eprintln!("[ARCHIVE] Failed to emit progress: {}", e);
+    }
+}
+
+/// Items matching `filter`, oldest first
+fn select(store: &Store, filter: &ExportFilter) -> Result<Vec<Item>, String> {
+    let items = store
+        .with_conn(|conn| {
+            let sql = format!(
+                "SELECT {} FROM item
+                 WHERE (?1 IS NULL OR source = ?1) AND (?2 IS NULL OR created_ts >= ?2) AND (?3 IS NULL OR created_ts < ?3)
+                 ORDER BY created_ts, id",
+                ITEM_COLUMNS
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(
+                rusqlite::params![filter.source, filter.after, filter.before],
+                Item::from_row,
+            )?;
+            rows.collect::<rusqlite::Result<Vec<Item>>>()
+        })
+        .map_err(|e| format!("Failed to load items: {}", e))?;
+
+    let mut filters: Vec<Filter> = filter.tags.iter().map(|t| Filter::Tag(t.clone())).collect();
+    filters.extend(filter.collection.iter().map(|c| Filter::Collection(c.clone())));
+    let matching = if filters.is_empty() {
+        None
+    } else {
+        Some(tags::matching(store, &filters).map_err(|e| format!("Failed to apply filters: {}", e))?)
+    };
+    let ids: Option<HashSet<i64>> = filter.ids.as_ref().map(|ids| ids.iter().copied().collect());
+
+    Ok(items
+        .into_iter()
+        .filter(|item| matching.as_ref().is_none_or(|m| m.contains(&item.id)))
+        .filter(|item| ids.as_ref().is_none_or(|ids| ids.contains(&item.id)))
+        .collect())
+}
+
+fn records(app: &AppHandle, items: Vec<Item>) -> Result<Vec<Record>, String> {
+    let store = app.state::<Store>();
+    let pinned = pins::pinned_ids(&store).map_err(|e| format!("Failed to load pins: {}", e))?;
+    let total = items.len();
+
+    let mut records = Vec::with_capacity(total);
+    for (i, item) in items.into_iter().enumerate() {
+        let tags = tags::for_item(&store, item.id).map_err(|e| format!("Failed to load tags: {}", e))?;
+        let collections =
+            tags::collections_for_item(&store, item.id).map_err(|e| format!("Failed to load collections: {}", e))?;
+        records.push(Record {
+            pinned: pinned.contains(&item.id),
+            item,
+            tags,
+            collections,
+            blob: None,
+        });
+        progress(app, "export", i + 1, total);
+    }
+    Ok(records)
+}
+
+/// A fence longer than any run of backticks in the text
+fn fence(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+fn write_markdown(out: &mut impl Write, records: &[Record]) -> std::io::Result<()> {
+    writeln!(out, "# ClipMind history")?;
+    writeln!(out)?;
+    writeln!(out, "Exported {}, {} items", storage::readable_time(now_ts()), records.len())?;
+
+    for record in records {
+        let item = &record.item;
+        writeln!(out)?;
+        let pin = if record.pinned { " (pinned)" } else { "" };
+        writeln!(out, "## {} · {}{}", item.readable_time, item.source, pin)?;
+        writeln!(out)?;
+        if !record.tags.is_empty() {
+            writeln!(out, "Tags: {}  ", record.tags.join(", "))?;
+        }
+        if !record.collections.is_empty() {
+            writeln!(out, "Collections: {}  ", record.collections.join(", "))?;
+        }
+        if let Some(blob_uri) = &item.blob_uri {
+            writeln!(out, "![{}](<{}>)", item.source, blob_uri)?;
+        }
+        if !record.tags.is_empty() || !record.collections.is_empty() || item.blob_uri.is_some() {
+            writeln!(out)?;
+        }
+        let fence = fence(&item.text);
+        writeln!(out, "{}", fence)?;
+        writeln!(out, "{}", item.text)?;
+        writeln!(out, "{}", fence)?;
+    }
+    Ok(())
+}
+
+/// history.json plus every readable blob. Returns (files bundled, files missing)
+fn write_zip(app: &AppHandle, file: File, records: &mut [Record]) -> Result<(usize, usize), String> {
+    let sandbox = app.state::<ImageSandbox>();
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    // Screenshots are already compressed, deflating them again only costs time
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let (mut bundled, mut missing) = (0, 0);
+    for record in records.iter_mut() {
+        let Some(blob_uri) = &record.item.blob_uri else {
+            continue;
+        };
+        let bytes = sandbox
+            .check(blob_uri)
+            .and_then(|path| sandbox.read(&path));
+        let bytes = match bytes {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("[ARCHIVE] Skipping file of item {}: {}", record.item.id, e);
+                missing += 1;
+                continue;
+            }
+        };
+        let file_name = Path::new(blob_uri)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "blob".to_string());
+        let name = format!("{}{}-{}", BUNDLE_BLOBS, record.item.id, file_name);
+        zip.start_file(name.as_str(), stored)
+            .and_then(|_| zip.write_all(&bytes).map_err(Into::into))
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        record.blob = Some(name);
+        bundled += 1;
+    }
+
+    let index = serde_json::to_vec_pretty(&Document {
+        version: VERSION,
+        exported_ts: now_ts(),
+        items: records,
+    })
+    .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    zip.start_file(BUNDLE_INDEX, deflated)
+        .and_then(|_| zip.write_all(&index).map_err(Into::into))
+        .and_then(|_| zip.finish())
+        .and_then(|mut out| out.flush().map_err(Into::into))
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    Ok((bundled, missing))
+}
+
+pub fn export(app: &AppHandle, format: Format, filter: &ExportFilter, path: &Path) -> Result<ExportReport, String> {
+    let items = select(&app.state::<Store>(), filter)?;
+    let mut records = records(app, items)?;
+
+    // Written next to the target first so a failed export never leaves half a file behind
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    let file = File::create(&tmp).map_err(|e| format!("Failed to create {}: {}", tmp.display(), e))?;
+
+    let result = match format {
+        Format::Zip => write_zip(app, file, &mut records),
+        Format::Json => {
+            let mut out = BufWriter::new(file);
+            let document = Document {
+                version: VERSION,
+                exported_ts: now_ts(),
+                items: &records,
+            };
+            serde_json::to_writer_pretty(&mut out, &document)
+                .map_err(|e| e.to_string())
+                .and_then(|_| out.flush().map_err(|e| e.to_string()))
+                .map(|_| (0, 0))
+        }
+        Format::Ndjson => {
+            let mut out = BufWriter::new(file);
+            records
+                .iter()
+                .try_for_each(|record| {
+                    serde_json::to_writer(&mut out, record).map_err(|e| e.to_string())?;
+                    writeln!(out).map_err(|e| e.to_string())
+                })
+                .and_then(|_| out.flush().map_err(|e| e.to_string()))
+                .map(|_| (0, 0))
+        }
+        Format::Markdown => {
+            let mut out = BufWriter::new(file);
+            write_markdown(&mut out, &records)
+                .and_then(|_| out.flush())
+                .map_err(|e| e.to_string())
+                .map(|_| (0, 0))
+        }
+    };
+
+    let (blobs, missing_blobs) = match result.and_then(|counts| {
+        fs::rename(&tmp, path)
+            .map(|_| counts)
+            .map_err(|e| e.to_string())
+    }) {
+        Ok(counts) => counts,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(format!("Failed to write {}: {}", path.display(), e));
+        }
+    };
+
+    eprintln!("[ARCHIVE] Exported {} items to {}", records.len(), path.display());
+    Ok(ExportReport {
+        path: path.display().to_string(),
+        format,
+        items: records.len(),
+        blobs,
+        missing_blobs,
+    })
+}
+
+/// The item list of a JSON document: ours, a bare array, or the old `{"history": [...]}`
+fn document_items(document: Value) -> Result<Vec<Value>, String> {
+    match document {
+        Value::Array(items) => Ok(items),
+        Value::Object(mut object) => match object.remove("items").or_else(|| object.remove("history")) {
+            Some(Value::Array(items)) => Ok(items),
+            _ => Err("No item list in the file".to_string()),
+        },
+        _ => Err("No item list in the file".to_string()),
+    }
+}
+
+fn parse_json(reader: impl Read) -> Result<Vec<Value>, String> {
+    let document = serde_json::from_reader(BufReader::new(reader)).map_err(|e| format!("Invalid JSON: {}", e))?;
+    document_items(document)
+}
+
+/// A line that isn't JSON becomes Null and gets counted as skipped
+fn parse_ndjson(reader: impl Read) -> Result<Vec<Value>, String> {
+    let mut items = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line.map_err(|e| format!("Failed to read file: {}", e))?;
+        if !line.trim().is_empty() {
+            items.push(serde_json::from_str(&line).unwrap_or(Value::Null));
+        }
+    }
+    Ok(items)
+}
+
+/// Unix seconds from a number (milliseconds when it's that big) or a date string
+fn parse_ts(value: &Value) -> Option<i64> {
+    let ts = match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64))?,
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(ts) = s.parse::<i64>() {
+                ts
+            } else if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                dt.timestamp()
+            } else {
+                let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok()?;
+                Local.from_local_datetime(&naive).earliest()?.timestamp()
+            }
+        }
+        _ => return None,
+    };
+    Some(if ts > 1_000_000_000_000 { ts / 1000 } else { ts })
+}
+
+/// Copy a bundled file into the blob store, named by its hash so importing twice doesn't duplicate it
+fn store_blob(app: &AppHandle, blob_dir: &Path, name: &str, hash: &str, bytes: &[u8]) -> Result<String, String> {
+    let ext = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "png".to_string());
+    let dest = blob_dir.join(format!("{}.{}", hash, ext));
+
+    if !dest.exists() {
+        fs::create_dir_all(blob_dir).map_err(|e| format!("Failed to create {}: {}", blob_dir.display(), e))?;
+        let tmp = dest.with_extension("tmp");
+        vault::blobs(app)
+            .write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, &dest))
+            .map_err(|e| format!("Failed to save {}: {}", dest.display(), e))?;
+    }
+    // Same form the sandbox hands out, so thumbnails and retention match it up
+    let path = fs::canonicalize(&dest).map_err(|e| format!("Failed to resolve {}: {}", dest.display(), e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn read_bundled(bundle: &mut ZipArchive<File>, name: &str, max_bytes: u64) -> Result<Vec<u8>, String> {
+    let mut entry = bundle.by_name(name).map_err(|e| format!("{} isn't in the bundle: {}", name, e))?;
+    if entry.size() > max_bytes {
+        return Err(format!("{} is bigger than the {} byte limit", name, max_bytes));
+    }
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(bytes)
+}
+
+struct Importer<'a> {
+    app: &'a AppHandle,
+    store: tauri::State<'a, Store>,
+    detector: tauri::State<'a, Detector>,
+    pinned: HashSet<i64>,
+    blob_dir: PathBuf,
+    max_blob_bytes: u64,
+}
+
+enum Outcome {
+    Imported { blob: bool },
+    Duplicate,
+}
+
+impl Importer<'_> {
+    fn import(&mut self, value: Value, bundle: Option<&mut ZipArchive<File>>) -> Result<Outcome, String> {
+        let incoming: Incoming = match value {
+            // Plain strings are taken as clipboard text
+            Value::String(text) => Incoming { text, ..Incoming::default() },
+            Value::Object(_) => serde_json::from_value(value).map_err(|e| format!("Not an item: {}", e))?,
+            _ => return Err("Not an item".to_string()),
+        };
+
+        let source = incoming.source.as_deref().unwrap_or("clipboard");
+        if !SOURCES.contains(&source) {
+            return Err(format!("Unknown source {:?}", source));
+        }
+
+        let bundled = match (&incoming.blob, bundle) {
+            (Some(name), Some(bundle)) => Some((name.as_str(), read_bundled(bundle, name, self.max_blob_bytes)?)),
+            _ => None,
+        };
+        // Only keep a path that still points at something on this machine
+        let linked = incoming.blob_uri.as_deref().filter(|uri| Path::new(uri).exists());
+        if incoming.text.is_empty() && bundled.is_none() && linked.is_none() {
+            return Err("Empty item".to_string());
+        }
+
+        let blob_hash = bundled.as_ref().map(|(_, bytes)| compute_hash(bytes));
+        let content_hash = incoming
+            .content_hash
+            .clone()
+            .filter(|h| !h.is_empty())
+            .or_else(|| blob_hash.clone())
+            .unwrap_or_else(|| compute_hash(incoming.text.as_bytes()));
+        let tags: Vec<String> = incoming
+            .tags
+            .iter()
+            .filter_map(|t| tags::normalize_tag(t).ok())
+            .collect();
+
+        // Before the duplicate check, same as a live copy
+        let scan = self.detector.scan(&incoming.text);
+        if scan.action == Action::Skip {
+            return Err(format!("Sensitive text ({} findings)", scan.findings.len()));
+        }
+
+        let existing = self
+            .store
+            .find_by_hash(&content_hash)
+            .map_err(|e| format!("Failed to look up duplicates: {}", e))?;
+        let (id, outcome) = match existing {
+            Some(id) => (id, Outcome::Duplicate),
+            None => {
+                // The file is only copied for items that are actually new
+                let blob_uri = match (&bundled, &blob_hash) {
+                    (Some((name, bytes)), Some(hash)) => Some(store_blob(self.app, &self.blob_dir, name, hash, bytes)?),
+                    _ => linked.map(str::to_string),
+                };
+                let created_ts = incoming.created_ts.as_ref().and_then(parse_ts).unwrap_or_else(now_ts);
+                let item = self
+                    .store
+                    .insert(NewItem {
+                        text: &scan.redacted,
+                        content_hash: &content_hash,
+                        source,
+                        blob_uri: blob_uri.as_deref(),
+                        created_ts,
+                    })
+                    .map_err(|e| format!("Failed to save item: {}", e))?;
+                if scan.findings.iter().any(|f| f.action == Action::Expire) {
+                    let secs = self.detector.config().expire_after_secs;
+                    if let Err(e) = sensitive::set_expiry(&self.store, item.id, secs) {
+                        // Rather lose the item than keep a secret around forever
+                        let _ = crate::retention::purge(self.app, &[item.id]);
+                        return Err(format!("Failed to set expiry: {}", e));
+                    }
+                }
+                fingerprint::remember(self.app, &item);
+                (item.id, Outcome::Imported { blob: bundled.is_some() })
+            }
+        };
+
+        // A tag or collection that doesn't make it only loses that, not the item
+        if let Err(e) = tags::add(&self.store, &[id], &tags) {
+            eprintln!("[ARCHIVE] Failed to tag item {}: {}", id, e);
+        }
+        for name in &incoming.collections {
+            if let Err(e) = tags::collect(&self.store, name, &[id]) {
+                eprintln!("[ARCHIVE] Failed to add item {} to {:?}: {}", id, name, e);
+            }
+        }
+        if incoming.pinned && self.pinned.insert(id) {
+            if let Err(e) = pins::pin(&self.store, id, None) {
+                eprintln!("[ARCHIVE] Failed to pin item {}: {}", id, e);
+            }
+        }
+
+        Ok(outcome)
+    }
+}
+
+pub fn import(app: &AppHandle, path: &Path) -> Result<ImportReport, String> {
+    if vault::is_locked(app) {
+        return Err("History is locked".to_string());
+    }
+    let format = Format::detect(path)?;
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+    let mut bundle = None;
+    let items = match format {
+        Format::Json => parse_json(file)?,
+        Format::Ndjson => parse_ndjson(file)?,
+        Format::Zip => {
+            let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a zip bundle: {}", e))?;
+            let items = parse_json(
+                archive
+                    .by_name(BUNDLE_INDEX)
+                    .map_err(|_| format!("No {} in the bundle", BUNDLE_INDEX))?,
+            )?;
+            bundle = Some(archive);
+            items
+        }
+        Format::Markdown => return Err("Markdown exports can't be imported".to_string()),
+    };
+
+    let store = app.state::<Store>();
+    let pinned = pins::pinned_ids(&store).map_err(|e| format!("Failed to load pins: {}", e))?;
+    let mut importer = Importer {
+        app,
+        store,
+        detector: app.state::<Detector>(),
+        pinned,
+        blob_dir: storage::blob_dir(app)?,
+        max_blob_bytes: settings::get(app).images.max_file_bytes,
+    };
+
+    let total = items.len();
+    let mut report = ImportReport {
+        format,
+        total,
+        imported: 0,
+        duplicates: 0,
+        skipped: 0,
+        blobs: 0,
+    };
+    for (i, value) in items.into_iter().enumerate() {
+        match importer.import(value, bundle.as_mut()) {
+            Ok(Outcome::Imported { blob }) => {
+                report.imported += 1;
+                report.blobs += blob as usize;
+            }
+            Ok(Outcome::Duplicate) => report.duplicates += 1,
+            Err(e) => {
+                eprintln!("[ARCHIVE] Skipping item {}: {}", i, e);
+                report.skipped += 1;
+            }
+        }
+        progress(app, "import", i + 1, total);
+    }
+
+    eprintln!(
+        "[ARCHIVE] Imported {} of {} items from {} ({} duplicates, {} skipped)",
+        report.imported,
+        total,
+        path.display(),
+        report.duplicates,
+        report.skipped
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn export_history(
+    app: AppHandle,
+    format: Format,
+    filter: Option<ExportFilter>,
+    path: String,
+) -> Result<ExportReport, String> {
+    if let Some(source) = filter
+        .as_ref()
+        .and_then(|f| f.source.as_deref())
+        .filter(|s| !SOURCES.contains(s))
+    {
+        return Err(format!("Unknown source {:?}", source));
+    }
+    let filter = filter.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || export(&app, format, &filter, Path::new(&path)))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn import_history(app: AppHandle, path: String) -> Result<ImportReport, String> {
+    tauri::async_runtime::spawn_blocking(move || import(&app, Path::new(&path)))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn fence_outgrows_backticks_in_text() {
+        assert_eq!(fence("plain text"), "```");
+        assert_eq!(fence("one ` two ``"), "```");
+        assert_eq!(fence("a ``` b"), "````");
+        assert_eq!(fence("`````"), "``````");
+    }
+
+    #[test]
+    fn parse_ts_seconds_and_milliseconds() {
+        assert_eq!(parse_ts(&json!(1_700_000_000)), Some(1_700_000_000));
+        assert_eq!(parse_ts(&json!(1_700_000_000_123i64)), Some(1_700_000_000));
+        assert_eq!(parse_ts(&json!(1_700_000_000.75)), Some(1_700_000_000));
+        assert_eq!(parse_ts(&json!("1700000000")), Some(1_700_000_000));
+    }
+
+    #[test]
+    fn parse_ts_dates() {
+        assert_eq!(parse_ts(&json!("2023-11-14T22:13:20Z")), Some(1_700_000_000));
+        assert_eq!(parse_ts(&json!("2023-11-14T23:13:20+01:00")), Some(1_700_000_000));
+
+        // readable_time is local time, same as storage::readable_time writes it
+        let local = Local
+            .with_ymd_and_hms(2024, 3, 5, 10, 11, 12)
+            .earliest()
+            .unwrap()
+            .timestamp();
+        assert_eq!(parse_ts(&json!("2024-03-05 10:11:12")), Some(local));
+    }
+
+    #[test]
+    fn parse_ts_rejects_garbage() {
+        assert_eq!(parse_ts(&json!("yesterday")), None);
+        assert_eq!(parse_ts(&json!(null)), None);
+        assert_eq!(parse_ts(&json!([1])), None);
+    }
+
+    #[test]
+    fn document_items_shapes() {
+        let ours = json!({"version": 1, "exported_ts": 0, "items": [{"text": "a"}, {"text": "b"}]});
+        assert_eq!(document_items(ours).unwrap().len(), 2);
+
+        let bare = json!([{"text": "a"}, "b", 3]);
+        assert_eq!(document_items(bare).unwrap().len(), 3);
+
+        let legacy = json!({"history": [{"content": "a", "timestamp": 1}]});
+        assert_eq!(document_items(legacy).unwrap().len(), 1);
+
+        assert!(document_items(json!({"items": "nope"})).is_err());
+        assert!(document_items(json!({"other": []})).is_err());
+        assert!(document_items(json!("text")).is_err());
+    }
+
+    #[test]
+    fn detect_by_extension() {
+        assert!(matches!(Format::detect(Path::new("a.json")), Ok(Format::Json)));
+        assert!(matches!(Format::detect(Path::new("a.ndjson")), Ok(Format::Ndjson)));
+        assert!(matches!(Format::detect(Path::new("A.JSONL")), Ok(Format::Ndjson)));
+        assert!(matches!(Format::detect(Path::new("dir.v2/a.zip")), Ok(Format::Zip)));
+        assert!(Format::detect(Path::new("a.md")).is_err());
+        assert!(Format::detect(Path::new("a.txt")).is_err());
+        assert!(Format::detect(Path::new("history")).is_err());
+    }
+}
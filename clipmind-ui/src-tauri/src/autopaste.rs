@@ -0,0 +1,419 @@
+// Auto-paste into the app that had focus before the quickboard opened.
+// The focused window is remembered when the quickboard is shown; after copy_item
+// hides it again, focus goes back to that window and Ctrl+V is synthesized.
+// Only X11 (XTest) for now, everything goes through `Paster` so other platforms
+// (or a fake in tests) can slot in
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::settings;
+
+/// Native window handle, an XID on X11
+pub type WindowId = u64;
+
+pub trait Paster: Send + Sync {
+    /// The window that has focus right now
+    fn focused_window(&self) -> Result<Option<WindowId>, String>;
+    /// Ask the window manager to give `window` focus
+    fn focus(&self, window: WindowId) -> Result<(), String>;
+    /// Send the paste keystroke to whatever has focus
+    fn paste(&self) -> Result<(), String>;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoPasteConfig {
+    pub enabled: bool,
+    /// Time the window manager gets to switch focus before the keystroke
+    pub delay_ms: u64,
+}
+
+impl Default for AutoPasteConfig {
+    fn default() -> Self {
+        AutoPasteConfig {
+            enabled: false,
+            delay_ms: 120,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AutoPasteStatus {
+    #[serde(flatten)]
+    pub config: AutoPasteConfig,
+    /// Why auto-paste can't work here, None when it can
+    pub unavailable: Option<String>,
+}
+
+pub struct AutoPaste {
+    paster: Result<Box<dyn Paster>, String>,
+    config: Mutex<AutoPasteConfig>,
+    previous: Mutex<Option<WindowId>>,
+}
+
+impl AutoPaste {
+    pub fn new(paster: Result<Box<dyn Paster>, String>, config: AutoPasteConfig) -> Self {
+        AutoPaste {
+            paster,
+            config: Mutex::new(config),
+            previous: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> AutoPasteConfig {
+        self.config.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_config(&self, config: AutoPasteConfig) {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    fn status(&self) -> AutoPasteStatus {
+        AutoPasteStatus {
+            config: self.config(),
+            unavailable: self.paster.as_ref().err().cloned(),
+        }
+    }
+
+    /// Note the focused window, called right before the quickboard shows
+    pub fn remember_focus(&self) {
+        let Ok(paster) = &self.paster else {
+            return;
+        };
+        let window = paster.focused_window().unwrap_or_else(|e| {
+            eprintln!("[AUTOPASTE] Can't read the focused window: {}", e);
+            None
+        });
+        *self.previous.lock().unwrap_or_else(|e| e.into_inner()) = window;
+    }
+
+    /// Focus the remembered window and paste into it, when enabled
+    pub fn paste_into_previous(&self) -> Result<(), String> {
+        let config = self.config();
+        if !config.enabled {
+            return Ok(());
+        }
+        let paster = self.paster.as_ref().map_err(Clone::clone)?;
+        let Some(window) = self.previous.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+            return Ok(());
+        };
+
+        paster.focus(window)?;
+        thread::sleep(Duration::from_millis(config.delay_ms));
+        paster.paste()
+    }
+
+    /// Run `copy`, then paste into the previous window if it worked.
+    /// A failed paste is only logged, the copy still stands
+    pub fn paste_after<T>(&self, copy: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        let copied = copy()?;
+        if let Err(e) = self.paste_into_previous() {
+            eprintln!("[AUTOPASTE] {}", e);
+        }
+        Ok(copied)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::xproto::{
+        AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, Keycode, Keysym, Window, KEY_PRESS_EVENT,
+        KEY_RELEASE_EVENT,
+    };
+    use x11rb::protocol::xtest::ConnectionExt as _;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::CURRENT_TIME;
+
+    use super::{Paster, WindowId};
+
+    const XK_CONTROL_L: Keysym = 0xffe3;
+    const XK_V: Keysym = 0x0076;
+
+    /// Focus through _NET_ACTIVE_WINDOW (EWMH), keys through the XTest extension
+    pub struct X11Paster {
+        conn: RustConnection,
+        root: Window,
+        net_active_window: u32,
+        control: Keycode,
+        v: Keycode,
+    }
+
+    fn err(e: impl std::fmt::Display) -> String {
+        format!("X11: {}", e)
+    }
+
+    impl X11Paster {
+        pub fn connect() -> Result<Self, String> {
+            let (conn, screen) = x11rb::connect(None).map_err(err)?;
+            let root = conn.setup().roots[screen].root;
+
+            conn.extension_information(x11rb::protocol::xtest::X11_EXTENSION_NAME)
+                .map_err(err)?
+                .ok_or("X11: the XTest extension is missing")?;
+
+            let net_active_window = conn
+                .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+                .map_err(err)?
+                .reply()
+                .map_err(err)?
+                .atom;
+
+            let control = keycode_for(&conn, XK_CONTROL_L)?;
+            let v = keycode_for(&conn, XK_V)?;
+
+            Ok(X11Paster {
+                conn,
+                root,
+                net_active_window,
+                control,
+                v,
+            })
+        }
+
+        fn key(&self, kind: u8, keycode: Keycode) -> Result<(), String> {
+            self.conn
+                .xtest_fake_input(kind, keycode, CURRENT_TIME, self.root, 0, 0, 0)
+                .map_err(err)?;
+            Ok(())
+        }
+    }
+
+    fn keycode_for(conn: &RustConnection, keysym: Keysym) -> Result<Keycode, String> {
+        let setup = conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let mapping = conn
+            .get_keyboard_mapping(min, max - min + 1)
+            .map_err(err)?
+            .reply()
+            .map_err(err)?;
+
+        let per = mapping.keysyms_per_keycode.max(1) as usize;
+        mapping
+            .keysyms
+            .chunks(per)
+            .position(|syms| syms.contains(&keysym))
+            .map(|i| min + i as u8)
+            .ok_or_else(|| format!("X11: no key for keysym {:#x}", keysym))
+    }
+
+    impl Paster for X11Paster {
+        fn focused_window(&self) -> Result<Option<WindowId>, String> {
+            let reply = self
+                .conn
+                .get_property(false, self.root, self.net_active_window, AtomEnum::WINDOW, 0, 1)
+                .map_err(err)?
+                .reply()
+                .map_err(err)?;
+            let active = reply.value32().and_then(|mut v| v.next()).filter(|&w| w != 0);
+            if let Some(window) = active {
+                return Ok(Some(window as WindowId));
+            }
+
+            // No EWMH window manager, fall back to the raw input focus
+            let focus = self.conn.get_input_focus().map_err(err)?.reply().map_err(err)?.focus;
+            Ok((focus > 1).then_some(focus as WindowId))
+        }
+
+        fn focus(&self, window: WindowId) -> Result<(), String> {
+            // Source 2 = pager, window managers honour those over plain app requests
+            let event = ClientMessageEvent::new(
+                32,
+                window as Window,
+                self.net_active_window,
+                [2, CURRENT_TIME, 0, 0, 0],
+            );
+            self.conn
+                .send_event(
+                    false,
+                    self.root,
+                    EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                    event,
+                )
+                .map_err(err)?;
+            self.conn.flush().map_err(err)
+        }
+
+        fn paste(&self) -> Result<(), String> {
+            self.key(KEY_PRESS_EVENT, self.control)?;
+            self.key(KEY_PRESS_EVENT, self.v)?;
+            self.key(KEY_RELEASE_EVENT, self.v)?;
+            self.key(KEY_RELEASE_EVENT, self.control)?;
+            self.conn.flush().map_err(err)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn platform_paster() -> Result<Box<dyn Paster>, String> {
+    x11::X11Paster::connect().map(|p| Box::new(p) as Box<dyn Paster>)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn platform_paster() -> Result<Box<dyn Paster>, String> {
+    Err("Auto-paste is only supported on Linux (X11) for now".to_string())
+}
+
+pub fn start(app: &AppHandle) {
+    let paster = platform_paster();
+    if let Err(e) = &paster {
+        eprintln!("[AUTOPASTE] Unavailable: {}", e);
+    }
+    app.manage(AutoPaste::new(paster, settings::get(app).autopaste));
+}
+
+/// Called when the quickboard is about to show
+pub fn remember_focus(app: &AppHandle) {
+    if let Some(autopaste) = app.try_state::<AutoPaste>() {
+        autopaste.remember_focus();
+    }
+}
+
+/// Used by copy_item, `copy` also hides the quickboard so focus can go back
+pub fn paste_after<T>(app: &AppHandle, copy: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    match app.try_state::<AutoPaste>() {
+        Some(autopaste) => autopaste.paste_after(copy),
+        None => copy(),
+    }
+}
+
+#[tauri::command]
+pub fn autopaste_settings(autopaste: State<'_, AutoPaste>) -> AutoPasteStatus {
+    autopaste.status()
+}
+
+#[tauri::command]
+pub fn set_autopaste(
+    app: AppHandle,
+    autopaste: State<'_, AutoPaste>,
+    enabled: bool,
+    delay_ms: Option<u64>,
+) -> Result<AutoPasteStatus, String> {
+    // Saving applies it too, through settings::apply
+    settings::update(&app, |settings| {
+        settings.autopaste.enabled = enabled;
+        if let Some(delay_ms) = delay_ms {
+            settings.autopaste.delay_ms = delay_ms.min(2000);
+        }
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(autopaste.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Records every call in a log shared with the test
+    struct FakePaster {
+        log: Arc<Mutex<Vec<String>>>,
+        focused: Option<WindowId>,
+        fail_focus: bool,
+    }
+
+    impl Paster for FakePaster {
+        fn focused_window(&self) -> Result<Option<WindowId>, String> {
+            self.log.lock().unwrap().push("focused_window".into());
+            Ok(self.focused)
+        }
+
+        fn focus(&self, window: WindowId) -> Result<(), String> {
+            self.log.lock().unwrap().push(format!("focus {}", window));
+            if self.fail_focus {
+                return Err("window is gone".into());
+            }
+            Ok(())
+        }
+
+        fn paste(&self) -> Result<(), String> {
+            self.log.lock().unwrap().push("paste".into());
+            Ok(())
+        }
+    }
+
+    fn autopaste(fail_focus: bool) -> (AutoPaste, Arc<Mutex<Vec<String>>>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let paster = FakePaster {
+            log: log.clone(),
+            focused: Some(42),
+            fail_focus,
+        };
+        let config = AutoPasteConfig {
+            enabled: true,
+            delay_ms: 0,
+        };
+        (AutoPaste::new(Ok(Box::new(paster)), config), log)
+    }
+
+    fn copy(log: &Arc<Mutex<Vec<String>>>, result: Result<&'static str, String>) -> Result<&'static str, String> {
+        log.lock().unwrap().push("copy".into());
+        result
+    }
+
+    #[test]
+    fn copies_then_focuses_then_pastes() {
+        let (autopaste, log) = autopaste(false);
+        autopaste.remember_focus();
+
+        assert_eq!(autopaste.paste_after(|| copy(&log, Ok("text/plain"))), Ok("text/plain"));
+        assert_eq!(*log.lock().unwrap(), ["focused_window", "copy", "focus 42", "paste"]);
+    }
+
+    #[test]
+    fn failed_copy_pastes_nothing() {
+        let (autopaste, log) = autopaste(false);
+        autopaste.remember_focus();
+
+        let result = autopaste.paste_after(|| copy(&log, Err("Item 7 not found".into())));
+        assert_eq!(result, Err("Item 7 not found".to_string()));
+        assert_eq!(*log.lock().unwrap(), ["focused_window", "copy"]);
+
+        // The window is still remembered for the next copy
+        assert_eq!(autopaste.paste_after(|| copy(&log, Ok("text/plain"))), Ok("text/plain"));
+        assert_eq!(log.lock().unwrap()[2..], ["copy", "focus 42", "paste"]);
+    }
+
+    #[test]
+    fn failed_focus_keeps_the_copy_and_skips_the_paste() {
+        let (autopaste, log) = autopaste(true);
+        autopaste.remember_focus();
+
+        assert_eq!(autopaste.paste_after(|| copy(&log, Ok("text/html"))), Ok("text/html"));
+        assert_eq!(*log.lock().unwrap(), ["focused_window", "copy", "focus 42"]);
+    }
+
+    #[test]
+    fn focus_error_is_reported() {
+        let (autopaste, _) = autopaste(true);
+        autopaste.remember_focus();
+        assert_eq!(autopaste.paste_into_previous(), Err("window is gone".to_string()));
+    }
+
+    #[test]
+    fn disabled_or_unavailable_only_copies() {
+        let (autopaste, log) = autopaste(false);
+        autopaste.remember_focus();
+        autopaste.set_config(AutoPasteConfig {
+            enabled: false,
+            delay_ms: 0,
+        });
+        assert_eq!(autopaste.paste_after(|| copy(&log, Ok("image/png"))), Ok("image/png"));
+        assert_eq!(*log.lock().unwrap(), ["focused_window", "copy"]);
+
+        let unavailable = AutoPaste::new(Err("no X11".into()), AutoPasteConfig::default());
+        unavailable.set_config(AutoPasteConfig {
+            enabled: true,
+            delay_ms: 0,
+        });
+        assert_eq!(unavailable.paste_after(|| Ok::<_, String>(1)), Ok(1));
+        assert_eq!(unavailable.paste_into_previous(), Err("no X11".to_string()));
+    }
+}
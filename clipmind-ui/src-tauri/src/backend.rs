@@ -0,0 +1,330 @@
+// Supervisor for the Python side of ClipMind
+// Spawns the FastAPI server and the OCR worker through tauri_plugin_shell,
+// restarts them with backoff when they crash and keeps their recent output around
+
+use std::collections::VecDeque;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+use zeroize::Zeroizing;
+
+use crate::{settings, vault};
+
+// Lines of stdout/stderr kept per process
+const LOG_CAPACITY: usize = 500;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// A process that stayed up this long is considered healthy again
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+
+struct ProcessSpec {
+    name: &'static str,
+    args: &'static [&'static str],
+}
+
+const PROCESSES: &[ProcessSpec] = &[
+    ProcessSpec {
+        name: "api",
+        args: &["-m", "uvicorn", "app.api.server:app", "--host", "127.0.0.1", "--port", "8000"],
+    },
+    // Capture is all on the Rust side (clipboard_watcher.rs, screenshot_watcher.rs),
+    // Python only runs OCR and the embeddings for the screenshots it queues
+    ProcessSpec {
+        name: "ocr",
+        args: &["-m", "app.ingest.ocr_worker"],
+    },
+];
+
+#[derive(Clone, Serialize)]
+pub struct LogLine {
+    pub stream: &'static str,
+    pub line: String,
+}
+
+#[derive(Default)]
+struct ProcessState {
+    child: Option<CommandChild>,
+    pid: Option<u32>,
+    restarts: u32,
+    last_exit_code: Option<i32>,
+    last_error: Option<String>,
+    restart_requested: bool,
+    logs: VecDeque<LogLine>,
+}
+
+impl ProcessState {
+    fn push_log(&mut self, stream: &'static str, bytes: &[u8]) {
+        if self.logs.len() == LOG_CAPACITY {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(LogLine {
+            stream,
+            line: String::from_utf8_lossy(bytes).trim_end().to_string(),
+        });
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProcessStatus {
+    pub name: &'static str,
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub last_exit_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub logs: Vec<LogLine>,
+}
+
+pub struct Backend {
+    root: Option<PathBuf>,
+    python: String,
+    processes: Vec<Mutex<ProcessState>>,
+    shutting_down: Mutex<bool>,
+}
+
+impl Backend {
+    fn new() -> Self {
+        Backend {
+            root: locate_project_root(),
+            python: python_command(),
+            processes: PROCESSES.iter().map(|_| Mutex::default()).collect(),
+            shutting_down: Mutex::new(false),
+        }
+    }
+
+    fn state(&self, index: usize) -> std::sync::MutexGuard<'_, ProcessState> {
+        self.processes[index].lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        *self.shutting_down.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Kill the process, its supervisor brings it straight back up
+    fn restart(&self, index: usize) {
+        let mut state = self.state(index);
+        state.restart_requested = true;
+        if let Some(child) = state.child.take() {
+            let _ = child.kill();
+        }
+    }
+
+    /// Kill every process, their supervisors start them again (after an unlock when locked)
+    pub fn restart_all(&self) {
+        for index in 0..PROCESSES.len() {
+            self.restart(index);
+        }
+    }
+
+    /// Kill every process for good, called when the app exits
+    pub fn shutdown(&self) {
+        *self.shutting_down.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        for index in 0..PROCESSES.len() {
+            if let Some(child) = self.state(index).child.take() {
+                let _ = child.kill();
+            }
+        }
+    }
+
+    fn status(&self, log_lines: usize) -> Vec<ProcessStatus> {
+        PROCESSES
+            .iter()
+            .enumerate()
+            .map(|(index, spec)| {
+                let state = self.state(index);
+                let skip = state.logs.len().saturating_sub(log_lines);
+                ProcessStatus {
+                    name: spec.name,
+                    running: state.child.is_some(),
+                    pid: state.pid,
+                    restarts: state.restarts,
+                    last_exit_code: state.last_exit_code,
+                    last_error: state.last_error.clone(),
+                    logs: state.logs.iter().skip(skip).cloned().collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Repo root with app/api/server.py. CLIPMIND_ROOT wins, otherwise
+/// search upwards from the working dir (the app runs from clipmind-ui/src-tauri in dev)
+pub(crate) fn locate_project_root() -> Option<PathBuf> {
+    if let Some(root) = env::var_os("CLIPMIND_ROOT") {
+        return Some(PathBuf::from(root));
+    }
+
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .find(|dir| dir.join("app").join("api").join("server.py").is_file())
+        .map(|dir| dir.to_path_buf())
+}
+
+fn python_command() -> String {
+    env::var("CLIPMIND_PYTHON").unwrap_or_else(|_| {
+        if cfg!(windows) {
+            "python".into()
+        } else {
+            "python3".into()
+        }
+    })
+}
+
+/// Create the supervisor and start every backend process.
+/// Set CLIPMIND_NO_BACKEND=1 when running the Python side by hand
+pub fn start(app: &AppHandle) {
+    app.manage(Backend::new());
+
+    if env::var_os("CLIPMIND_NO_BACKEND").is_some() {
+        return;
+    }
+
+    for (index, spec) in PROCESSES.iter().enumerate() {
+        let app = app.clone();
+        thread::Builder::new()
+            .name(format!("backend-{}", spec.name))
+            .spawn(move || supervise(app, index))
+            .expect("failed to spawn backend supervisor");
+    }
+}
+
+fn supervise(app: AppHandle, index: usize) {
+    let backend = app.state::<Backend>();
+    let spec = &PROCESSES[index];
+
+    let Some(root) = backend.root.clone() else {
+        backend.state(index).last_error =
+            Some("ClipMind project root not found, set CLIPMIND_ROOT".into());
+        return;
+    };
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut first_start = true;
+
+    while !backend.is_shutting_down() {
+        // An encrypted clipmind.db can't be opened without the key, wait for an unlock
+        if vault::is_locked(&app) {
+            thread::sleep(Duration::from_millis(250));
+            continue;
+        }
+
+        let mut command = app
+            .shell()
+            .command(&backend.python)
+            .args(spec.args)
+            .current_dir(&root)
+            .env("PYTHONUNBUFFERED", "1");
+        // app/core/config.py reads its [search] section from here
+        if let Some(path) = settings::path(&app) {
+            command = command.env("CLIPMIND_SETTINGS", path);
+        }
+        // app/db/session.py opens the db through SQLCipher when this is set. The key itself
+        // goes over stdin, anything of the same user can read a child's environment
+        let key = vault::db_key_hex(&app);
+        if key.is_some() {
+            command = command.env("CLIPMIND_DB_KEY_STDIN", "1");
+        }
+        let spawned = command.spawn();
+
+        let started = Instant::now();
+
+        match spawned {
+            Ok((mut rx, mut child)) => {
+                if let Some(key) = key {
+                    // Sized up front so no unzeroed copy is left behind by a reallocation
+                    let mut line = Zeroizing::new(Vec::with_capacity(key.len() + 1));
+                    line.extend_from_slice(key.as_bytes());
+                    line.push(b'\n');
+                    if let Err(e) = child.write(&line) {
+                        backend.state(index).last_error = Some(format!("Failed to pass the history key: {}", e));
+                    }
+                }
+                {
+                    let mut state = backend.state(index);
+                    if !first_start {
+                        state.restarts += 1;
+                    }
+                    state.pid = Some(child.pid());
+                    state.child = Some(child);
+                    state.last_error = None;
+                    state.restart_requested = false;
+                }
+
+                while let Some(event) = rx.blocking_recv() {
+                    let mut state = backend.state(index);
+                    match event {
+                        CommandEvent::Stdout(bytes) => state.push_log("stdout", &bytes),
+                        CommandEvent::Stderr(bytes) => state.push_log("stderr", &bytes),
+                        CommandEvent::Error(e) => state.last_error = Some(e),
+                        CommandEvent::Terminated(payload) => {
+                            state.last_exit_code = payload.code;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+
+                let mut state = backend.state(index);
+                state.child = None;
+                state.pid = None;
+            }
+            Err(e) => {
+                backend.state(index).last_error =
+                    Some(format!("Failed to start {}: {}", backend.python, e));
+            }
+        }
+        first_start = false;
+
+        let manual = std::mem::take(&mut backend.state(index).restart_requested);
+        if manual || started.elapsed() >= STABLE_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+        if manual {
+            continue;
+        }
+
+        wait_backoff(&backend, index, backoff);
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Sleep for `backoff`, cut short by a manual restart or shutdown
+fn wait_backoff(backend: &Backend, index: usize, backoff: Duration) {
+    let deadline = Instant::now() + backoff;
+    while Instant::now() < deadline {
+        if backend.is_shutting_down() || backend.state(index).restart_requested {
+            return;
+        }
+        thread::sleep(Duration::from_millis(250));
+    }
+}
+
+#[tauri::command]
+pub fn backend_status(backend: State<'_, Backend>, log_lines: Option<usize>) -> Vec<ProcessStatus> {
+    backend.status(log_lines.unwrap_or(50))
+}
+
+/// Restart one process by name, or all of them when no name is given
+#[tauri::command]
+pub fn backend_restart(backend: State<'_, Backend>, name: Option<String>) -> Result<(), String> {
+    let mut matched = false;
+    for (index, spec) in PROCESSES.iter().enumerate() {
+        if name.as_deref().is_none_or(|n| n == spec.name) {
+            backend.restart(index);
+            matched = true;
+        }
+    }
+
+    if !matched {
+        return Err(format!("Unknown backend process: {}", name.unwrap_or_default()));
+    }
+    Ok(())
+}
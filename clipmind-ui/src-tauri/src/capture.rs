@@ -0,0 +1,150 @@
+// Capture state machine: recording, or paused (optionally until a deadline)
+// Every Rust ingestion path checks `is_active` (not paused, vault unlocked) before saving anything.
+// Changes are broadcast to all windows and reflected in the tray
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::storage::{now_ts, readable_time};
+use crate::{tray, vault};
+
+pub const STATE_EVENT: &str = "capture://state";
+
+// Longest timed pause, anything longer should be an explicit pause
+const MAX_PAUSE_SECS: u64 = 24 * 3600;
+
+#[derive(Clone, Copy, Serialize, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CaptureState {
+    Recording,
+    /// `resume_at` is a unix timestamp, None means until resumed by hand
+    Paused { resume_at: Option<i64> },
+}
+
+struct Inner {
+    state: CaptureState,
+    // Bumped on every change so a stale resume timer knows to do nothing
+    generation: u64,
+}
+
+pub struct Capture {
+    inner: Mutex<Inner>,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Capture {
+            inner: Mutex::new(Inner {
+                state: CaptureState::Recording,
+                generation: 0,
+            }),
+        }
+    }
+}
+
+impl Capture {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn state(&self) -> CaptureState {
+        self.lock().state
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self.state(), CaptureState::Paused { .. })
+    }
+
+    /// Swap in a new state, returns its generation
+    fn set(&self, state: CaptureState) -> u64 {
+        let mut inner = self.lock();
+        inner.state = state;
+        inner.generation += 1;
+        inner.generation
+    }
+}
+
+/// Whether capture is paused, the state the tray toggle flips
+pub fn is_paused(app: &AppHandle) -> bool {
+    app.state::<Capture>().is_paused()
+}
+
+/// Shorthand for the ingestion paths. A locked history can't take new items either
+pub fn is_active(app: &AppHandle) -> bool {
+    !is_paused(app) && !vault::is_locked(app)
+}
+
+/// Tray label / tooltip for the current state
+pub fn describe(state: CaptureState) -> String {
+    match state {
+        CaptureState::Recording => "Recording".to_string(),
+        CaptureState::Paused { resume_at: None } => "Paused".to_string(),
+        CaptureState::Paused { resume_at: Some(ts) } => {
+            // Just the time of day, the date is noise for a pause this short
+            let time = readable_time(ts);
+            format!("Paused until {}", time.split(' ').nth(1).unwrap_or(&time))
+        }
+    }
+}
+
+/// Pause capture, for `duration` seconds or until resumed when None
+pub fn pause(app: &AppHandle, duration: Option<u64>) -> CaptureState {
+    let duration = duration.map(|secs| secs.clamp(1, MAX_PAUSE_SECS));
+    let state = CaptureState::Paused {
+        resume_at: duration.map(|secs| now_ts() + secs as i64),
+    };
+    let generation = app.state::<Capture>().set(state);
+    broadcast(app, state);
+
+    if let Some(secs) = duration {
+        let app = app.clone();
+        thread::Builder::new()
+            .name("capture-resume".into())
+            .spawn(move || {
+                thread::sleep(Duration::from_secs(secs));
+                // Only resume if nothing changed the state in the meantime
+                let capture = app.state::<Capture>();
+                if capture.lock().generation == generation {
+                    resume(&app);
+                }
+            })
+            .expect("failed to spawn capture resume timer");
+    }
+
+    state
+}
+
+pub fn resume(app: &AppHandle) -> CaptureState {
+    let state = CaptureState::Recording;
+    app.state::<Capture>().set(state);
+    broadcast(app, state);
+    state
+}
+
+fn broadcast(app: &AppHandle, state: CaptureState) {
+    eprintln!("[CAPTURE] {}", describe(state));
+    if let Err(e) = app.emit(STATE_EVENT, state) {
+        eprintln!("[CAPTURE] Failed to emit state: {}", e);
+    }
+    tray::refresh(app);
+}
+
+/// Pause for `duration` seconds, or indefinitely when omitted
+#[tauri::command]
+pub fn pause_capture(app: AppHandle, duration: Option<u64>) -> CaptureState {
+    pause(&app, duration)
+}
+
+#[tauri::command]
+pub fn resume_capture(app: AppHandle) -> CaptureState {
+    resume(&app)
+}
+
+#[tauri::command]
+pub fn capture_state(capture: State<'_, Capture>) -> CaptureState {
+    capture.state()
+}
@@ -0,0 +1,308 @@
+// Native clipboard watcher, replaces the pyperclip loop in app/ingest/main.py
+// Polls the clipboard on a background thread, saves new non-junk text, copied
+// files and images to the item table and emits every capture to the webviews.
+// Formats beyond plain text go to item_representation (see representations.rs)
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use xxhash_rust::xxh64::xxh64;
+
+use crate::representations::{self, LastCopy, Representation, RichClipboard};
+use crate::sensitive::{self, Action, Detector};
+use crate::{capture, fingerprint, retention, tags};
+use crate::storage::{now_ts, NewItem, Store};
+
+pub const CAPTURED_EVENT: &str = "clipboard://captured";
+
+const POLL_MS: u64 = 500;
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Captured {
+    Text {
+        id: i64,
+        text: String,
+        content_hash: String,
+        created_ts: i64,
+    },
+    Files {
+        id: i64,
+        paths: Vec<String>,
+        content_hash: String,
+        created_ts: i64,
+    },
+    Image {
+        id: i64,
+        width: u32,
+        height: u32,
+        content_hash: String,
+        created_ts: i64,
+    },
+}
+
+/// Same rules as `is_junk` in app/ingest/main.py, keep the two in sync
+pub fn is_junk(text: &str) -> bool {
+    // Rule 1: too short (less than 5 characters)
+    if text.chars().count() < 5 {
+        return true;
+    }
+
+    // Rule 2: Only whitespace
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return true;
+    }
+
+    // Rule 3: Only repeated characters (AAAA, 1111)
+    let mut chars = trimmed.chars();
+    if let Some(first) = chars.next() {
+        if chars.all(|c| c == first) {
+            return true;
+        }
+    }
+
+    // Rule 4: Only emojis or special characters (no alphanumeric)
+    if !text.chars().any(char::is_alphanumeric) {
+        return true;
+    }
+
+    // Rule 5: Common boilerplate patterns
+    let junk_patterns = ["copied to clipboard", "copy successful", "ctrl+c", "cmd+c"];
+    let lower = text.to_lowercase();
+    junk_patterns.iter().any(|p| lower.contains(p))
+}
+
+/// xxhash64 hex digest, matches `compute_hash` on the Python side
+pub fn compute_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", xxh64(bytes, 0))
+}
+
+/// Start polling the clipboard on a background thread.
+/// Must not run on the main thread, arboard can deadlock there on Linux
+pub fn start(app: AppHandle) {
+    thread::Builder::new()
+        .name("clipboard-watcher".into())
+        .spawn(move || watch(app))
+        .expect("failed to spawn clipboard watcher");
+}
+
+fn watch(app: AppHandle) {
+    let mut last_hash: Option<String> = None;
+
+    loop {
+        if let Some(captured) = poll_once(&app, &mut last_hash) {
+            if let Err(e) = app.emit(CAPTURED_EVENT, &captured) {
+                eprintln!("[CLIPBOARD] Failed to emit capture: {}", e);
+            }
+        }
+
+        thread::sleep(Duration::from_millis(POLL_MS));
+    }
+}
+
+/// Remember `content_hash` as the latest clipboard content, false when it already was
+/// or when ClipMind put it there itself (copying an item back)
+fn is_new(app: &AppHandle, last_hash: &mut Option<String>, content_hash: &str) -> bool {
+    if last_hash.as_deref() == Some(content_hash) {
+        return false;
+    }
+    *last_hash = Some(content_hash.to_string());
+    !app.state::<LastCopy>().is(content_hash)
+}
+
+/// Read the clipboard once, returns a capture when the content changed and isn't junk
+fn poll_once(app: &AppHandle, last_hash: &mut Option<String>) -> Option<Captured> {
+    let clipboard = app.clipboard();
+    let rich = app.state::<RichClipboard>();
+
+    // Files first, file managers often put the paths on as plain text too.
+    // The hash is still tracked while paused so whatever was copied
+    // during the pause isn't picked up on resume
+    let files = rich.read_files();
+    if !files.is_empty() {
+        let uri_list = representations::uri_list(&files);
+        let content_hash = compute_hash(uri_list.as_bytes());
+        if !is_new(app, last_hash, &content_hash) || !capture::is_active(app) {
+            return None;
+        }
+        return save_files(app, &files, &uri_list, &content_hash);
+    }
+
+    // Then text, only look at the (much bigger) image when there's no text
+    if let Ok(raw) = clipboard.read_text() {
+        let text = raw.trim();
+        if !text.is_empty() {
+            let content_hash = compute_hash(text.as_bytes());
+            if !is_new(app, last_hash, &content_hash) || !capture::is_active(app) || is_junk(text) {
+                return None;
+            }
+
+            return save_text(app, text, rich.read_html(), rich.read_rtf(), &content_hash);
+        }
+    }
+
+    let image = clipboard.read_image().ok()?;
+    let content_hash = compute_hash(image.rgba());
+    if !is_new(app, last_hash, &content_hash) || !capture::is_active(app) {
+        return None;
+    }
+
+    save_image(app, image.rgba(), image.width(), image.height(), &content_hash)
+}
+
+/// True when the item is already stored, errors count as stored so nothing is saved twice
+fn is_duplicate(store: &Store, content_hash: &str) -> bool {
+    match store.find_by_hash(content_hash) {
+        Ok(found) => found.is_some(),
+        Err(e) => {
+            eprintln!("[CLIPBOARD] Duplicate check failed: {}", e);
+            true
+        }
+    }
+}
+
+/// Copied files are stored as their paths, one per line, with the URI list to restore them
+fn save_files(app: &AppHandle, files: &[PathBuf], uri_list: &str, content_hash: &str) -> Option<Captured> {
+    let store = app.state::<Store>();
+    if is_duplicate(&store, content_hash) {
+        return None;
+    }
+
+    let paths: Vec<String> = files.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+    let item = store
+        .insert(NewItem {
+            text: &paths.join("\n"),
+            content_hash,
+            source: "clipboard",
+            blob_uri: None,
+            created_ts: now_ts(),
+        })
+        .map_err(|e| eprintln!("[CLIPBOARD] Failed to save item: {}", e))
+        .ok()?;
+
+    if let Err(e) = representations::save(&store, item.id, &[Representation::new(representations::URI_LIST, uri_list)]) {
+        eprintln!("[CLIPBOARD] Failed to save file list: {}", e);
+    }
+    fingerprint::remember(app, &item);
+    tags::auto_tag(app, &item);
+
+    Some(Captured::Files {
+        id: item.id,
+        paths,
+        content_hash: item.content_hash,
+        created_ts: item.created_ts,
+    })
+}
+
+/// Copied images are stored as PNG, the item text is just a label to show and search
+fn save_image(app: &AppHandle, rgba: &[u8], width: u32, height: u32, content_hash: &str) -> Option<Captured> {
+    let store = app.state::<Store>();
+    if is_duplicate(&store, content_hash) {
+        return None;
+    }
+
+    let png = representations::encode_png(rgba, width, height)
+        .map_err(|e| eprintln!("[CLIPBOARD] {}", e))
+        .ok()?;
+    let item = store
+        .insert(NewItem {
+            text: &format!("[Image {}x{}]", width, height),
+            content_hash,
+            source: "clipboard",
+            blob_uri: None,
+            created_ts: now_ts(),
+        })
+        .map_err(|e| eprintln!("[CLIPBOARD] Failed to save item: {}", e))
+        .ok()?;
+
+    if let Err(e) = representations::save(&store, item.id, &[Representation::new(representations::PNG, png)]) {
+        // An image item without its image is useless
+        eprintln!("[CLIPBOARD] Failed to save image, dropping item: {}", e);
+        let _ = store.delete(item.id);
+        return None;
+    }
+    tags::auto_tag(app, &item);
+
+    Some(Captured::Image {
+        id: item.id,
+        width,
+        height,
+        content_hash: item.content_hash,
+        created_ts: item.created_ts,
+    })
+}
+
+/// Insert clipboard text unless an exact duplicate is already stored.
+/// Secrets are skipped, redacted or stored with an expiry first
+fn save_text(
+    app: &AppHandle,
+    text: &str,
+    html: Option<String>,
+    rtf: Option<Vec<u8>>,
+    content_hash: &str,
+) -> Option<Captured> {
+    let store = app.state::<Store>();
+    let detector = app.state::<Detector>();
+
+    let scan = detector.scan(text);
+    if scan.action == Action::Skip {
+        eprintln!("[CLIPBOARD] Skipped sensitive capture ({} findings)", scan.findings.len());
+        return None;
+    }
+
+    // Keyed on the original text so copying the same secret again is still a duplicate
+    if is_duplicate(&store, content_hash) {
+        return None;
+    }
+
+    let item = store
+        .insert(NewItem {
+            text: &scan.redacted,
+            content_hash,
+            source: "clipboard",
+            blob_uri: None,
+            created_ts: now_ts(),
+        })
+        .map_err(|e| eprintln!("[CLIPBOARD] Failed to save item: {}", e))
+        .ok()?;
+
+    fingerprint::remember(app, &item);
+
+    // HTML and RTF would still have the secret in them, only keep them for clean text
+    if scan.findings.is_empty() {
+        let formatted: Vec<Representation> = [
+            html.map(|html| Representation::new(representations::HTML, html)),
+            rtf.map(|rtf| Representation::new(representations::RTF, rtf)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if let Err(e) = representations::save(&store, item.id, &formatted) {
+            eprintln!("[CLIPBOARD] Failed to save formatted text: {}", e);
+        }
+    }
+
+    if scan.findings.iter().any(|f| f.action == Action::Expire) {
+        let secs = detector.config().expire_after_secs;
+        if let Err(e) = sensitive::set_expiry(&store, item.id, secs) {
+            // Rather lose the item than keep a secret around forever
+            eprintln!("[CLIPBOARD] Failed to set expiry, dropping item: {}", e);
+            // Takes the formatted copies with it
+            let _ = retention::purge(app, &[item.id]);
+            return None;
+        }
+    }
+    tags::auto_tag(app, &item);
+
+    Some(Captured::Text {
+        id: item.id,
+        text: item.text,
+        content_hash: item.content_hash,
+        created_ts: item.created_ts,
+    })
+}
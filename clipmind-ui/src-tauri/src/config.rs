@@ -0,0 +1,31 @@
+// The app config dir, and the per-module JSON files from before settings.toml.
+// Those are only read once now, to import them into settings.toml
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use tauri::{AppHandle, Manager};
+
+pub fn config_path(app: &AppHandle, file: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve config dir: {}", e))?;
+    Ok(dir.join(file))
+}
+
+/// Load `file`, falling back to defaults when it is missing or unreadable
+pub fn load<T: DeserializeOwned + Default>(app: &AppHandle, file: &str) -> T {
+    let Ok(path) = config_path(app, file) else {
+        return T::default();
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+            eprintln!("[CONFIG] Ignoring invalid {}: {}", path.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
@@ -0,0 +1,434 @@
+// Duplicate detection over the item table
+// Exact matches share a content_hash (xxhash64, same as the Python side).
+// Near matches compare 64-bit fingerprints by Hamming distance: SimHash over
+// word shingles for text, dHash + pHash for screenshots (see fingerprint.rs)
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use xxhash_rust::xxh64::xxh64;
+
+use crate::clipboard_watcher::compute_hash;
+use crate::fingerprint::{self, hamming, is_image, Fingerprint};
+use crate::image_sandbox::ImageSandbox;
+use crate::pins;
+use crate::retention;
+use crate::search::tokenize;
+use crate::storage::{Item, Store};
+
+// Words per shingle, and the fewest words for a SimHash to mean anything
+const SHINGLE: usize = 3;
+const MIN_TOKENS: usize = 4;
+
+// Max differing bits out of 64 to still count as a near duplicate
+const TEXT_MAX_DISTANCE: u32 = 3;
+const PHASH_MAX_DISTANCE: u32 = 10;
+const DHASH_MAX_DISTANCE: u32 = 12;
+
+#[derive(Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Exact,
+    Near,
+}
+
+#[derive(Serialize)]
+pub struct Duplicate {
+    #[serde(flatten)]
+    pub item: Item,
+    pub kind: MatchKind,
+    /// Differing fingerprint bits, 0 for exact matches
+    pub distance: u32,
+    pub similarity: f32,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateGroup {
+    pub keep: i64,
+    pub remove: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct DedupeReport {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub groups: Vec<DuplicateGroup>,
+    pub removed: usize,
+}
+
+/// SimHash over overlapping word shingles, None for text too short to compare
+pub fn simhash(text: &str) -> Option<u64> {
+    let tokens = tokenize(text);
+    if tokens.len() < MIN_TOKENS {
+        return None;
+    }
+
+    let mut weights = [0i32; 64];
+    for shingle in tokens.windows(SHINGLE) {
+        let hash = xxh64(shingle.join(" ").as_bytes(), 0);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, &w)| w > 0)
+            .fold(0u64, |acc, (bit, _)| acc | (1 << bit)),
+    )
+}
+
+/// Exact key for an item, rows from before migrate_add_hash.py fall back to the text
+fn exact_key(item: &Item) -> String {
+    if item.content_hash.is_empty() {
+        compute_hash(item.text.as_bytes())
+    } else {
+        item.content_hash.clone()
+    }
+}
+
+/// Every item with its fingerprint, filling in the cache for new items
+fn all_fingerprints(app: &AppHandle) -> Result<Vec<(Item, Fingerprint)>, String> {
+    let store = app.state::<Store>();
+    let sandbox = app.state::<ImageSandbox>();
+
+    let items = store
+        .items_after_id(0)
+        .map_err(|e| format!("Failed to load items: {}", e))?;
+    let mut cached = fingerprint::load_all(&store).map_err(|e| format!("Failed to load fingerprints: {}", e))?;
+
+    let mut out = Vec::with_capacity(items.len());
+    for item in items {
+        let fp = match cached.remove(&item.id) {
+            Some(fp) => fp,
+            None => {
+                let fp = fingerprint::compute(&sandbox, &item);
+                fingerprint::save(&store, item.id, &fp).map_err(|e| format!("Failed to cache fingerprint: {}", e))?;
+                fp
+            }
+        };
+        out.push((item, fp));
+    }
+    Ok(out)
+}
+
+/// Hamming distance when `a` and `b` are near duplicates
+fn near_distance(a: &(Item, Fingerprint), b: &(Item, Fingerprint)) -> Option<u32> {
+    let (item_a, fp_a) = a;
+    let (item_b, fp_b) = b;
+
+    match (is_image(item_a), is_image(item_b)) {
+        (true, true) => {
+            let p = hamming(fp_a.phash?, fp_b.phash?);
+            let d = hamming(fp_a.dhash?, fp_b.dhash?);
+            // pHash decides, dHash weeds out layouts that only look alike in frequency space
+            (p <= PHASH_MAX_DISTANCE && d <= DHASH_MAX_DISTANCE).then_some(p)
+        }
+        (false, false) => {
+            let distance = hamming(fp_a.simhash?, fp_b.simhash?);
+            (distance <= TEXT_MAX_DISTANCE).then_some(distance)
+        }
+        _ => None,
+    }
+}
+
+fn classify(a: &(Item, Fingerprint), b: &(Item, Fingerprint)) -> Option<(MatchKind, u32)> {
+    if exact_key(&a.0) == exact_key(&b.0) {
+        return Some((MatchKind::Exact, 0));
+    }
+    near_distance(a, b).map(|d| (MatchKind::Near, d))
+}
+
+/// Split a 64-bit hash into `count` bands. Hashes within `count - 1` bits of each
+/// other agree on at least one band, there are only that many differing bits to go around
+fn bands(hash: u64, count: u32) -> Vec<(u32, u64)> {
+    let mut out = Vec::with_capacity(count as usize);
+    let mut start = 0;
+    for band in 0..count {
+        let width = 64 / count + u32::from(band < 64 % count);
+        out.push((band, (hash >> start) & ((1u64 << width) - 1)));
+        start += width;
+    }
+    out
+}
+
+/// LSH buckets an entry goes in, only entries sharing one can be near duplicates
+fn bucket_keys((item, fp): &(Item, Fingerprint)) -> Vec<(bool, u32, u64)> {
+    let image = is_image(item);
+    let (hash, max_distance) = if image {
+        (fp.phash, PHASH_MAX_DISTANCE)
+    } else {
+        (fp.simhash, TEXT_MAX_DISTANCE)
+    };
+    let Some(hash) = hash else {
+        return Vec::new();
+    };
+    bands(hash, max_distance + 1)
+        .into_iter()
+        .map(|(band, value)| (image, band, value))
+        .collect()
+}
+
+/// Group duplicates, keeping the most recent item of each group.
+/// Pinned items are never removed, a group with one keeps its latest pinned item.
+///
+/// Every group has a leader, the item it keeps, and everything else in it is a
+/// duplicate of that leader itself. No chains: A~B and B~C doesn't put A and C together
+fn group(entries: &[(Item, Fingerprint)], include_near: bool, pinned: &HashSet<i64>) -> Vec<DuplicateGroup> {
+    let rank = |i: usize| {
+        let item = &entries[i].0;
+        (pinned.contains(&item.id), item.created_ts, item.id)
+    };
+
+    // Exact matches by key first, each set of those moves as one unit, best item first
+    let mut units: Vec<Vec<usize>> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let unit = *by_key.entry(exact_key(&entry.0)).or_insert_with(|| {
+            units.push(Vec::new());
+            units.len() - 1
+        });
+        units[unit].push(i);
+    }
+    for members in &mut units {
+        members.sort_by_key(|&i| Reverse(rank(i)));
+    }
+
+    // Best first, so a leader always outranks whatever joins it
+    let mut order: Vec<usize> = (0..units.len()).collect();
+    order.sort_by_key(|&unit| Reverse(rank(units[unit][0])));
+
+    let mut buckets: HashMap<(bool, u32, u64), Vec<usize>> = HashMap::new();
+    if include_near {
+        for &unit in &order {
+            for key in bucket_keys(&entries[units[unit][0]]) {
+                buckets.entry(key).or_default().push(unit);
+            }
+        }
+    }
+
+    let mut assigned = vec![false; units.len()];
+    let mut groups = Vec::new();
+    for &leader in &order {
+        if assigned[leader] {
+            continue;
+        }
+        assigned[leader] = true;
+        let lead = &entries[units[leader][0]];
+        let mut members = units[leader].clone();
+
+        if include_near {
+            for key in bucket_keys(lead) {
+                for &unit in buckets.get(&key).into_iter().flatten() {
+                    if !assigned[unit] && near_distance(lead, &entries[units[unit][0]]).is_some() {
+                        assigned[unit] = true;
+                        members.extend(&units[unit]);
+                    }
+                }
+            }
+        }
+
+        let remove: Vec<i64> = members[1..]
+            .iter()
+            .map(|&i| entries[i].0.id)
+            .filter(|id| !pinned.contains(id))
+            .collect();
+        if !remove.is_empty() {
+            groups.push(DuplicateGroup {
+                keep: lead.0.id,
+                remove,
+            });
+        }
+    }
+    groups
+}
+
+/// Items that are exact or near duplicates of `item_id`, closest first
+#[tauri::command]
+pub async fn find_duplicates(app: AppHandle, item_id: i64) -> Result<Vec<Duplicate>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = all_fingerprints(&app)?;
+        let target = entries
+            .iter()
+            .find(|(item, _)| item.id == item_id)
+            .ok_or_else(|| format!("Item {} not found", item_id))?;
+
+        let mut duplicates: Vec<Duplicate> = entries
+            .iter()
+            .filter(|(item, _)| item.id != item_id)
+            .filter_map(|other| {
+                let (kind, distance) = classify(target, other)?;
+                Some(Duplicate {
+                    item: other.0.clone(),
+                    kind,
+                    distance,
+                    similarity: 1.0 - distance as f32 / 64.0,
+                })
+            })
+            .collect();
+
+        duplicates.sort_by_key(|d| (d.distance, -d.item.created_ts));
+        Ok(duplicates)
+    })
+    .await
+    .map_err(|e| format!("Duplicate search failed: {}", e))?
+}
+
+/// Collapse every duplicate group down to its most recent item.
+/// With `dry_run` nothing is deleted, the report shows what would be
+#[tauri::command]
+pub async fn dedupe_history(app: AppHandle, dry_run: bool, include_near: Option<bool>) -> Result<DedupeReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = all_fingerprints(&app)?;
+        let pinned = pins::pinned_ids(&app.state::<Store>()).map_err(|e| format!("Failed to load pins: {}", e))?;
+        let groups = group(&entries, include_near.unwrap_or(true), &pinned);
+
+        let mut removed = 0;
+        if !dry_run {
+            let ids: Vec<i64> = groups.iter().flat_map(|g| g.remove.iter().copied()).collect();
+            removed = retention::purge(&app, &ids)?.items;
+        }
+
+        Ok(DedupeReport {
+            dry_run,
+            scanned: entries.len(),
+            groups,
+            removed,
+        })
+    })
+    .await
+    .map_err(|e| format!("Dedupe failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "Remember to send the quarterly report to finance before the Friday meeting, \
+        attach the updated revenue spreadsheet, copy the regional managers and ask them to \
+        confirm the headcount numbers for their teams by the end of the week";
+
+    fn text_entry(id: i64, text: &str, created_ts: i64) -> (Item, Fingerprint) {
+        let item = Item {
+            id,
+            text: text.to_string(),
+            content_hash: String::new(),
+            source: "clipboard".to_string(),
+            blob_uri: None,
+            created_ts,
+            readable_time: String::new(),
+        };
+        let fp = Fingerprint {
+            simhash: simhash(text),
+            ..Fingerprint::default()
+        };
+        (item, fp)
+    }
+
+    #[test]
+    fn simhash_distance_near_and_far() {
+        let a = simhash(NOTE).unwrap();
+        let near = simhash(&format!("{} please", NOTE)).unwrap();
+        let far = simhash("The recipe calls for two cups of flour, a pinch of salt and three eggs").unwrap();
+
+        assert_eq!(simhash(&NOTE.to_uppercase()), Some(a));
+        assert!(hamming(a, near) <= TEXT_MAX_DISTANCE);
+        assert!(hamming(a, far) > TEXT_MAX_DISTANCE);
+        assert_eq!(simhash("too short"), None);
+    }
+
+    #[test]
+    fn bands_cover_every_bit() {
+        let hash = 0x0123_4567_89ab_cdef;
+        let parts = bands(hash, 4);
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], (0, 0xcdef));
+        assert_eq!(parts[3], (3, 0x0123));
+
+        // 64 bits don't split evenly into 11 bands, the first ones are a bit wider
+        let widths: u32 = bands(u64::MAX, 11).iter().map(|(_, v)| v.count_ones()).sum();
+        assert_eq!(widths, 64);
+    }
+
+    #[test]
+    fn close_hashes_share_a_band() {
+        let hash = 0xdead_beef_cafe_f00d;
+        let shared = |other: u64| {
+            bands(hash, TEXT_MAX_DISTANCE + 1)
+                .iter()
+                .zip(bands(other, TEXT_MAX_DISTANCE + 1))
+                .any(|(a, b)| *a == b)
+        };
+
+        // Three flipped bits can't touch all four bands
+        assert!(shared(hash ^ (1 | 1 << 20 | 1 << 40)));
+        assert!(shared(hash ^ (1 << 62 | 1 << 63 | 1 << 5)));
+        // One per band leaves nothing in common, but that's past the max distance anyway
+        assert!(!shared(hash ^ (1 | 1 << 16 | 1 << 32 | 1 << 48)));
+    }
+
+    #[test]
+    fn bucket_keys_only_for_hashed_entries() {
+        let entry = text_entry(1, NOTE, 0);
+        let keys = bucket_keys(&entry);
+        assert_eq!(keys.len(), TEXT_MAX_DISTANCE as usize + 1);
+        assert!(keys.iter().all(|&(image, _, _)| !image));
+
+        assert!(bucket_keys(&text_entry(2, "hi", 0)).is_empty());
+    }
+
+    #[test]
+    fn group_keeps_most_recent_not_lowest_id() {
+        let entries = [
+            text_entry(1, NOTE, 100),
+            text_entry(2, NOTE, 300),
+            text_entry(3, NOTE, 200),
+            text_entry(4, "something else entirely, nothing like the others", 400),
+        ];
+        let groups = group(&entries, false, &HashSet::new());
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep, 2);
+        assert_eq!(groups[0].remove, [3, 1]);
+    }
+
+    #[test]
+    fn group_pulls_in_near_duplicates() {
+        let entries = [
+            text_entry(1, NOTE, 100),
+            text_entry(2, &format!("{} please", NOTE), 50),
+            text_entry(3, NOTE, 20),
+        ];
+        let groups = group(&entries, false, &HashSet::new());
+        assert_eq!(groups.len(), 1);
+        assert_eq!((groups[0].keep, &groups[0].remove[..]), (1, &[3][..]));
+
+        let groups = group(&entries, true, &HashSet::new());
+        assert_eq!(groups.len(), 1);
+        assert_eq!((groups[0].keep, &groups[0].remove[..]), (1, &[3, 2][..]));
+    }
+
+    #[test]
+    fn group_keeps_pinned_items() {
+        let entries = [
+            text_entry(1, NOTE, 100),
+            text_entry(2, NOTE, 300),
+            text_entry(3, NOTE, 200),
+        ];
+
+        // A pinned item leads even when it's older
+        let groups = group(&entries, false, &HashSet::from([3]));
+        assert_eq!((groups[0].keep, &groups[0].remove[..]), (3, &[2, 1][..]));
+
+        // And is never removed when another pin leads
+        let groups = group(&entries, false, &HashSet::from([1, 3]));
+        assert_eq!((groups[0].keep, &groups[0].remove[..]), (3, &[2][..]));
+    }
+}
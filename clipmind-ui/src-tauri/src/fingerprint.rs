@@ -0,0 +1,406 @@
+// Per-item fingerprints and the screenshot ingest gate
+// Screenshots get a pHash (plus a dHash for dedup.rs), text gets a SimHash.
+// They live in item_fingerprint next to the item. New screenshots are rejected
+// when their pHash is within `max_distance` bits of a recent one
+//
+// This is the only place screenshots get inserted. Accepted ones go in with
+// OCR_PENDING as their text and a row in ocr_job, app/ingest/ocr_worker.py takes
+// jobs from there, fills in the OCR text and embeddings and deletes the job.
+// Rejected ones are recorded in capture_reject
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use xxhash_rust::xxh64::Xxh64;
+
+use crate::image_sandbox::ImageSandbox;
+use crate::storage::{now_ts, Item, NewItem, Store};
+use crate::{capture, dedup, ledger, settings, tags};
+
+/// Text of a screenshot item until the OCR worker has run on it
+pub const OCR_PENDING: &str = "[OCR pending]";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item_fingerprint (
+    item_id INTEGER NOT NULL PRIMARY KEY,
+    simhash INTEGER,
+    dhash INTEGER,
+    phash INTEGER
+);
+CREATE TABLE IF NOT EXISTS capture_reject (
+    content_hash VARCHAR NOT NULL PRIMARY KEY,
+    path VARCHAR NOT NULL,
+    duplicate_of INTEGER NOT NULL,
+    distance INTEGER NOT NULL,
+    created_ts INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS ocr_job (
+    item_id INTEGER NOT NULL PRIMARY KEY,
+    path VARCHAR NOT NULL,
+    queued_ts INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error VARCHAR
+);
+";
+
+// Screenshots from before ocr_job existed, or inserted while the worker was down
+const QUEUE_PENDING: &str = "
+INSERT OR IGNORE INTO ocr_job (item_id, path, queued_ts)
+SELECT id, blob_uri, created_ts FROM item WHERE source = 'screenshot' AND text = ?1 AND blob_uri IS NOT NULL
+";
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FingerprintConfig {
+    /// Screenshots within this many differing pHash bits (of 64) are duplicates
+    pub max_distance: u32,
+    /// How many of the latest screenshots a new one is compared against
+    pub compare_recent: u32,
+}
+
+impl Default for FingerprintConfig {
+    fn default() -> Self {
+        FingerprintConfig {
+            // Survives a moved cursor or a ticking clock, not a different window
+            max_distance: 6,
+            compare_recent: 200,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Fingerprint {
+    pub simhash: Option<u64>,
+    pub dhash: Option<u64>,
+    pub phash: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum IngestOutcome {
+    Inserted { item_id: i64 },
+    /// Same file bytes as an existing item
+    Duplicate { item_id: i64 },
+    /// Looks the same as an existing screenshot
+    NearDuplicate { item_id: i64, distance: u32 },
+    Paused,
+}
+
+pub fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Difference hash: 9x8 grayscale, one bit per horizontal gradient
+pub fn dhash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let bit = y * 8 + x;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1 << bit;
+            }
+        }
+    }
+    hash
+}
+
+/// Perceptual hash: low 8x8 frequencies of a 32x32 DCT against their median
+pub fn phash(img: &DynamicImage) -> u64 {
+    const N: usize = 32;
+    const LOW: usize = 8;
+
+    let small = img.resize_exact(N as u32, N as u32, FilterType::Triangle).to_luma8();
+    let pixels: Vec<f64> = small.pixels().map(|p| p[0] as f64).collect();
+
+    // cos((2x + 1) * u * pi / 2N) for the frequencies we keep
+    let mut cos = [[0f64; N]; LOW];
+    for (u, row) in cos.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            *c = ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI / (2 * N) as f64).cos();
+        }
+    }
+
+    let mut coeffs = [0f64; LOW * LOW];
+    for v in 0..LOW {
+        for u in 0..LOW {
+            let mut sum = 0.0;
+            for y in 0..N {
+                for x in 0..N {
+                    sum += pixels[y * N + x] * cos[u][x] * cos[v][y];
+                }
+            }
+            coeffs[v * LOW + u] = sum;
+        }
+    }
+
+    // The DC term is just the average brightness, leave it out of the median
+    let mut rest = coeffs[1..].to_vec();
+    rest.sort_by(f64::total_cmp);
+    let median = rest[rest.len() / 2];
+
+    coeffs
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c > median)
+        .fold(0u64, |acc, (bit, _)| acc | (1 << bit))
+}
+
+/// xxhash64 of the file bytes, same as compute_image_hash in screenshot_watcher.py
+pub fn file_hash(path: &Path) -> std::io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Xxh64::new(0);
+    let mut buf = vec![0u8; 1024 * 1024];
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:016x}", hasher.digest()))
+}
+
+pub fn is_image(item: &Item) -> bool {
+    item.source == "screenshot" && item.blob_uri.is_some()
+}
+
+pub fn of_image(img: &DynamicImage) -> Fingerprint {
+    Fingerprint {
+        dhash: Some(dhash(img)),
+        phash: Some(phash(img)),
+        ..Default::default()
+    }
+}
+
+pub fn compute(sandbox: &ImageSandbox, item: &Item) -> Fingerprint {
+    if !is_image(item) {
+        return Fingerprint {
+            simhash: dedup::simhash(&item.text),
+            ..Default::default()
+        };
+    }
+
+    let path = item.blob_uri.as_deref().unwrap_or_default();
+    match sandbox.open(path) {
+        Ok(img) => of_image(&img),
+        Err(e) => {
+            // Cached as empty so a missing file isn't retried on every call
+            eprintln!("[FINGERPRINT] Can't fingerprint item {}: {}", item.id, e);
+            Fingerprint::default()
+        }
+    }
+}
+
+fn from_columns(simhash: Option<i64>, dhash: Option<i64>, phash: Option<i64>) -> Fingerprint {
+    Fingerprint {
+        simhash: simhash.map(|h| h as u64),
+        dhash: dhash.map(|h| h as u64),
+        phash: phash.map(|h| h as u64),
+    }
+}
+
+/// Every cached fingerprint, dropping rows whose item is gone
+pub fn load_all(store: &Store) -> rusqlite::Result<HashMap<i64, Fingerprint>> {
+    store.with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM item_fingerprint WHERE item_id NOT IN (SELECT id FROM item)",
+            [],
+        )?;
+
+        let mut stmt = conn.prepare("SELECT item_id, simhash, dhash, phash FROM item_fingerprint")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get(0)?, from_columns(row.get(1)?, row.get(2)?, row.get(3)?)))
+        })?;
+        rows.collect()
+    })
+}
+
+pub fn save(store: &Store, item_id: i64, fp: &Fingerprint) -> rusqlite::Result<()> {
+    store.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO item_fingerprint (item_id, simhash, dhash, phash) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                item_id,
+                fp.simhash.map(|h| h as i64),
+                fp.dhash.map(|h| h as i64),
+                fp.phash.map(|h| h as i64),
+            ],
+        )?;
+        Ok(())
+    })
+}
+
+/// Fingerprint a freshly inserted item so later lookups don't have to
+pub fn remember(app: &AppHandle, item: &Item) {
+    let fp = compute(&app.state::<ImageSandbox>(), item);
+    if let Err(e) = save(&app.state::<Store>(), item.id, &fp) {
+        eprintln!("[FINGERPRINT] Failed to cache fingerprint: {}", e);
+    }
+}
+
+/// pHash of the latest `limit` screenshots, fingerprinting any that are missing
+fn recent_screenshot_hashes(app: &AppHandle, limit: u32) -> rusqlite::Result<Vec<(i64, u64)>> {
+    let store = app.state::<Store>();
+    let rows: Vec<(i64, Option<i64>, bool)> = store.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT item.id, item_fingerprint.phash, item_fingerprint.item_id IS NOT NULL
+             FROM item LEFT JOIN item_fingerprint ON item_fingerprint.item_id = item.id
+             WHERE item.source = 'screenshot' AND item.blob_uri IS NOT NULL
+             ORDER BY item.created_ts DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect()
+    })?;
+
+    let mut hashes = Vec::with_capacity(rows.len());
+    for (id, phash, cached) in rows {
+        if cached {
+            hashes.extend(phash.map(|h| (id, h as u64)));
+            continue;
+        }
+
+        // Inserted by the Python watcher, fingerprint it once now
+        if let Some(item) = store.get(id)? {
+            let fp = compute(&app.state::<ImageSandbox>(), &item);
+            save(&store, id, &fp)?;
+            hashes.extend(fp.phash.map(|h| (id, h)));
+        }
+    }
+    Ok(hashes)
+}
+
+fn queue_ocr(store: &Store, item_id: i64, path: &str) -> rusqlite::Result<()> {
+    store.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO ocr_job (item_id, path, queued_ts) VALUES (?1, ?2, ?3)",
+            params![item_id, path, now_ts()],
+        )?;
+        Ok(())
+    })
+}
+
+fn record_reject(store: &Store, content_hash: &str, path: &str, duplicate_of: i64, distance: u32) -> rusqlite::Result<()> {
+    store.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO capture_reject (content_hash, path, duplicate_of, distance, created_ts)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![content_hash, path, duplicate_of, distance, now_ts()],
+        )?;
+        Ok(())
+    })
+}
+
+fn rejected(store: &Store, content_hash: &str) -> rusqlite::Result<Option<(i64, u32)>> {
+    store.with_conn(|conn| {
+        conn.query_row(
+            "SELECT duplicate_of, distance FROM capture_reject WHERE content_hash = ?1",
+            [content_hash],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+    })
+}
+
+/// Run a new screenshot through the duplicate checks and insert it when it's new
+pub fn ingest(app: &AppHandle, path: &str) -> Result<IngestOutcome, String> {
+    if !capture::is_active(app) {
+        return Ok(IngestOutcome::Paused);
+    }
+
+    let store = app.state::<Store>();
+    let sandbox = app.state::<ImageSandbox>();
+    let config = settings::get(app).fingerprint;
+
+    let canonical = sandbox.check(path).map_err(|e| e.to_string())?;
+    let (content_hash, outcome) = match file_hash(&canonical) {
+        Ok(hash) => {
+            let outcome = ingest_file(app, &config, &canonical, &hash);
+            (Some(hash), outcome)
+        }
+        Err(e) => (None, Err(format!("Failed to hash {}: {}", path, e))),
+    };
+
+    ledger::record(&store, &canonical, content_hash.as_deref(), &outcome);
+    outcome
+}
+
+fn ingest_file(app: &AppHandle, config: &FingerprintConfig, canonical: &Path, content_hash: &str) -> Result<IngestOutcome, String> {
+    let store = app.state::<Store>();
+    let sandbox = app.state::<ImageSandbox>();
+    let path_str = canonical.to_string_lossy().into_owned();
+
+    if let Some(item_id) = store
+        .find_by_hash(content_hash)
+        .map_err(|e| format!("Duplicate check failed: {}", e))?
+    {
+        return Ok(IngestOutcome::Duplicate { item_id });
+    }
+    if let Some((item_id, distance)) =
+        rejected(&store, content_hash).map_err(|e| format!("Duplicate check failed: {}", e))?
+    {
+        return Ok(IngestOutcome::NearDuplicate { item_id, distance });
+    }
+
+    let img = sandbox.decode(canonical).map_err(|e| e.to_string())?;
+    let fp = of_image(&img);
+    let phash = fp.phash.unwrap_or_default();
+
+    let recent = recent_screenshot_hashes(app, config.compare_recent)
+        .map_err(|e| format!("Failed to load screenshot fingerprints: {}", e))?;
+    let closest = recent
+        .iter()
+        .map(|&(id, other)| (id, hamming(phash, other)))
+        .min_by_key(|&(_, distance)| distance);
+
+    if let Some((item_id, distance)) = closest.filter(|&(_, d)| d <= config.max_distance) {
+        record_reject(&store, content_hash, &path_str, item_id, distance)
+            .map_err(|e| format!("Failed to record rejected capture: {}", e))?;
+        eprintln!(
+            "[FINGERPRINT] Rejected {} as a near duplicate of item {} ({} bits)",
+            path_str, item_id, distance
+        );
+        return Ok(IngestOutcome::NearDuplicate { item_id, distance });
+    }
+
+    let item = store
+        .insert(NewItem {
+            text: OCR_PENDING,
+            content_hash,
+            source: "screenshot",
+            blob_uri: Some(&path_str),
+            created_ts: now_ts(),
+        })
+        .map_err(|e| format!("Failed to save screenshot: {}", e))?;
+    save(&store, item.id, &fp).map_err(|e| format!("Failed to save fingerprint: {}", e))?;
+    queue_ocr(&store, item.id, &path_str).map_err(|e| format!("Failed to queue OCR: {}", e))?;
+    tags::auto_tag(app, &item);
+
+    Ok(IngestOutcome::Inserted { item_id: item.id })
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    app.state::<Store>()
+        .with_conn(|conn| {
+            conn.execute_batch(SCHEMA)?;
+            conn.execute(QUEUE_PENDING, [OCR_PENDING])
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to create fingerprint tables: {}", e))
+}
+
+#[tauri::command]
+pub async fn ingest_screenshot(app: AppHandle, path: String) -> Result<IngestOutcome, String> {
+    tauri::async_runtime::spawn_blocking(move || ingest(&app, &path))
+        .await
+        .map_err(|e| format!("Ingest task failed: {}", e))?
+}
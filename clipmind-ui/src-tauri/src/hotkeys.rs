@@ -0,0 +1,213 @@
+// Global hotkeys, registered from Rust instead of App.tsx
+// Bindings live in the [hotkeys] section of settings.toml
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Manager, State, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::{quickboard, settings};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    ToggleQuickboard,
+}
+
+impl HotkeyAction {
+    /// Name used in settings.toml
+    pub fn key(self) -> &'static str {
+        match self {
+            HotkeyAction::ToggleQuickboard => "toggle_quickboard",
+        }
+    }
+
+    fn run(self, app: &AppHandle) {
+        match self {
+            HotkeyAction::ToggleQuickboard => quickboard::toggle(app),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub bindings: BTreeMap<HotkeyAction, String>,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        HotkeyConfig {
+            bindings: BTreeMap::from([(
+                HotkeyAction::ToggleQuickboard,
+                "CommandOrControl+Shift+V".to_string(),
+            )]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyState {
+    Registered,
+    /// Not a valid accelerator string
+    Invalid,
+    /// Another action already uses the same keys
+    Duplicate,
+    /// The OS refused it, usually because another app holds it
+    Unavailable,
+}
+
+#[derive(Clone, Serialize)]
+pub struct HotkeyStatus {
+    pub action: HotkeyAction,
+    pub shortcut: String,
+    pub state: HotkeyState,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    config: HotkeyConfig,
+    // Registered shortcut id -> action, looked up by the plugin handler
+    active: HashMap<u32, (Shortcut, HotkeyAction)>,
+    statuses: Vec<HotkeyStatus>,
+}
+
+#[derive(Default)]
+pub struct Hotkeys {
+    inner: Mutex<Inner>,
+}
+
+impl Hotkeys {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Bindings as last applied, including ones that failed to register
+    pub fn config(&self) -> HotkeyConfig {
+        self.lock().config.clone()
+    }
+
+    fn action_for(&self, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        self.lock().active.get(&shortcut.id()).map(|(_, action)| *action)
+    }
+}
+
+/// Global shortcut plugin with a single handler that dispatches to the bound action
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            if let Some(action) = app.state::<Hotkeys>().action_for(shortcut) {
+                action.run(app);
+            }
+        })
+        .build()
+}
+
+/// Register the bindings from settings
+pub fn start(app: &AppHandle) {
+    app.manage(Hotkeys::default());
+    apply(app, settings::get(app).hotkeys);
+}
+
+/// Swap in a new set of bindings, reporting per-binding problems in the statuses.
+/// The state lock is never held while talking to the OS, the plugin handler
+/// needs it from the main thread
+pub fn apply(app: &AppHandle, config: HotkeyConfig) -> Vec<HotkeyStatus> {
+    let hotkeys = app.state::<Hotkeys>();
+    let global_shortcut = app.global_shortcut();
+
+    let previous: Vec<Shortcut> = {
+        let mut inner = hotkeys.lock();
+        inner.active.drain().map(|(_, (shortcut, _))| shortcut).collect()
+    };
+    for shortcut in previous {
+        let _ = global_shortcut.unregister(shortcut);
+    }
+
+    let mut active = HashMap::new();
+    let mut statuses = Vec::new();
+
+    for (&action, accelerator) in &config.bindings {
+        let mut status = HotkeyStatus {
+            action,
+            shortcut: accelerator.clone(),
+            state: HotkeyState::Registered,
+            error: None,
+        };
+
+        match Shortcut::from_str(accelerator) {
+            Err(e) => {
+                status.state = HotkeyState::Invalid;
+                status.error = Some(e.to_string());
+            }
+            Ok(shortcut) => {
+                if let Some((_, other)) = active.get(&shortcut.id()) {
+                    status.state = HotkeyState::Duplicate;
+                    status.error = Some(format!("Already bound to {:?}", other));
+                } else if let Err(e) = global_shortcut.register(shortcut) {
+                    status.state = HotkeyState::Unavailable;
+                    status.error = Some(e.to_string());
+                } else {
+                    active.insert(shortcut.id(), (shortcut, action));
+                }
+            }
+        }
+
+        statuses.push(status);
+    }
+
+    let mut inner = hotkeys.lock();
+    inner.config = config;
+    inner.active = active;
+    inner.statuses = statuses.clone();
+    statuses
+}
+
+#[tauri::command]
+pub fn list_hotkeys(hotkeys: State<'_, Hotkeys>) -> Vec<HotkeyStatus> {
+    hotkeys.lock().statuses.clone()
+}
+
+/// Rebind `action`. The binding is only saved when the OS accepted it
+#[tauri::command]
+pub fn set_hotkey(app: AppHandle, action: HotkeyAction, shortcut: String) -> Result<Vec<HotkeyStatus>, String> {
+    let mut config = app.state::<Hotkeys>().config();
+    let previous = config.bindings.insert(action, shortcut.clone());
+
+    let statuses = apply(&app, config.clone());
+    let failed = statuses
+        .iter()
+        .find(|s| s.action == action && s.state != HotkeyState::Registered);
+
+    if let Some(failed) = failed {
+        let error = format!(
+            "Can't bind {}: {}",
+            shortcut,
+            failed.error.clone().unwrap_or_default()
+        );
+
+        // Put the old binding back so the user isn't left without a hotkey
+        match previous {
+            Some(old) => config.bindings.insert(action, old),
+            None => config.bindings.remove(&action),
+        };
+        apply(&app, config);
+        return Err(error);
+    }
+
+    settings::update(&app, |settings| {
+        settings.hotkeys = config;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(statuses)
+}
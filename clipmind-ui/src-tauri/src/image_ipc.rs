@@ -0,0 +1,151 @@
+// Image bytes over IPC as raw binary instead of a JSON number array
+// (a 4K screenshot used to be ~100MB of JSON). Every payload starts with a small
+// header, all integers little endian:
+//
+//   0..4   magic "CMIM"
+//   4      header version (1)
+//   5      format, 1 = RGBA8 pixels, 2 = PNG file
+//   6..8   reserved
+//   8..12  width
+//   12..16 height
+//   16..24 payload length in bytes
+//
+// In JS `invoke` resolves to an ArrayBuffer for these commands. Big images can
+// also be streamed through a Channel: one header message, then payload chunks
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{Channel, InvokeResponseBody, Response};
+use tauri::{AppHandle, Manager};
+
+use crate::image_sandbox::{ImageError, ImageSandbox};
+
+const MAGIC: &[u8; 4] = b"CMIM";
+const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 24;
+
+const DEFAULT_CHUNK: usize = 4 * 1024 * 1024;
+const MIN_CHUNK: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelFormat {
+    Rgba8,
+    Png,
+}
+
+impl PixelFormat {
+    fn code(self) -> u8 {
+        match self {
+            PixelFormat::Rgba8 => 1,
+            PixelFormat::Png => 2,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct StreamSummary {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub bytes: usize,
+    pub chunks: usize,
+}
+
+fn header(format: PixelFormat, width: u32, height: u32, len: usize) -> [u8; HEADER_LEN] {
+    let mut out = [0u8; HEADER_LEN];
+    out[0..4].copy_from_slice(MAGIC);
+    out[4] = VERSION;
+    out[5] = format.code();
+    out[8..12].copy_from_slice(&width.to_le_bytes());
+    out[12..16].copy_from_slice(&height.to_le_bytes());
+    out[16..24].copy_from_slice(&(len as u64).to_le_bytes());
+    out
+}
+
+/// Header followed by the payload, one buffer for a single IPC response
+pub fn frame(format: PixelFormat, width: u32, height: u32, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(&header(format, width, height, payload.len()));
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Width and height from an encoded image's header
+pub fn dimensions(encoded: &[u8]) -> Result<(u32, u32), ImageError> {
+    ImageReader::new(Cursor::new(encoded))
+        .with_guessed_format()
+        .map_err(|e| ImageError::Decode(e.to_string()))?
+        .into_dimensions()
+        .map_err(|e| ImageError::Decode(e.to_string()))
+}
+
+fn encode(img: DynamicImage, format: PixelFormat) -> Result<(u32, u32, Vec<u8>), ImageError> {
+    let (width, height) = (img.width(), img.height());
+    let bytes = match format {
+        PixelFormat::Rgba8 => img.into_rgba8().into_raw(),
+        PixelFormat::Png => {
+            let mut out = Cursor::new(Vec::new());
+            img.write_to(&mut out, ImageFormat::Png)
+                .map_err(|e| ImageError::Encode(e.to_string()))?;
+            out.into_inner()
+        }
+    };
+    Ok((width, height, bytes))
+}
+
+/// Full-size RGBA8 pixels of an image inside the sandbox, as a framed binary response
+#[tauri::command]
+pub async fn read_image_file(app: AppHandle, path: String) -> Result<Response, ImageError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        // Only files inside the allowed folders, within the size limits
+        let img = app.state::<ImageSandbox>().open(&path)?;
+        let (width, height, rgba) = encode(img, PixelFormat::Rgba8)?;
+        Ok(Response::new(frame(PixelFormat::Rgba8, width, height, &rgba)))
+    })
+    .await
+    .map_err(|e| ImageError::Io(format!("Image task failed: {}", e)))?
+}
+
+/// Send an image through `on_chunk`: the header first, then the payload in chunks
+/// of `chunk_bytes`. Resolves once everything was sent
+#[tauri::command]
+pub async fn stream_image_file(
+    app: AppHandle,
+    path: String,
+    format: Option<PixelFormat>,
+    chunk_bytes: Option<usize>,
+    on_chunk: Channel<InvokeResponseBody>,
+) -> Result<StreamSummary, ImageError> {
+    let format = format.unwrap_or(PixelFormat::Rgba8);
+    let chunk_bytes = chunk_bytes.unwrap_or(DEFAULT_CHUNK).max(MIN_CHUNK);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let img = app.state::<ImageSandbox>().open(&path)?;
+        let (width, height, bytes) = encode(img, format)?;
+
+        let send = |body: Vec<u8>| {
+            on_chunk
+                .send(InvokeResponseBody::Raw(body))
+                .map_err(|e| ImageError::Io(format!("Failed to send image data: {}", e)))
+        };
+        send(header(format, width, height, bytes.len()).to_vec())?;
+        let mut chunks = 0;
+        for chunk in bytes.chunks(chunk_bytes) {
+            send(chunk.to_vec())?;
+            chunks += 1;
+        }
+
+        Ok(StreamSummary {
+            width,
+            height,
+            format,
+            bytes: bytes.len(),
+            chunks,
+        })
+    })
+    .await
+    .map_err(|e| ImageError::Io(format!("Image task failed: {}", e)))?
+}
@@ -0,0 +1,223 @@
+// Guards every image the webview asks Rust to open
+// Only files under an allow-listed root are readable, symlinks and `..` can't
+// escape it, and oversized files or pixel counts are refused before decoding
+
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, Cursor, Seek};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use image::{DynamicImage, ImageReader, Limits};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::vault::{self, Blobs};
+use crate::{screenshot_watcher, settings, storage, thumbnail};
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
+pub enum ImageError {
+    NotFound(String),
+    Traversal(String),
+    OutsideAllowedRoots(String),
+    NotAFile(String),
+    TooLarge { bytes: u64, limit: u64 },
+    TooManyPixels { width: u32, height: u32, limit: u64 },
+    Decode(String),
+    Encode(String),
+    Io(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::NotFound(p) => write!(f, "Image not found: {}", p),
+            ImageError::Traversal(p) => write!(f, "Path traversal rejected: {}", p),
+            ImageError::OutsideAllowedRoots(p) => write!(f, "Path is outside the allowed folders: {}", p),
+            ImageError::NotAFile(p) => write!(f, "Not a file: {}", p),
+            ImageError::TooLarge { bytes, limit } => {
+                write!(f, "Image is {} bytes, limit is {}", bytes, limit)
+            }
+            ImageError::TooManyPixels { width, height, limit } => {
+                write!(f, "Image is {}x{}, limit is {} pixels", width, height, limit)
+            }
+            ImageError::Decode(e) => write!(f, "Failed to decode image: {}", e),
+            ImageError::Encode(e) => write!(f, "Failed to encode image: {}", e),
+            ImageError::Io(e) => write!(f, "Failed to read image: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Folders on top of the built-in ones (Pictures, OneDrive Pictures, the blob store)
+    pub extra_roots: Vec<PathBuf>,
+    pub max_file_bytes: u64,
+    pub max_pixels: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        SandboxConfig {
+            extra_roots: Vec::new(),
+            max_file_bytes: 100 * 1024 * 1024,
+            // Roomy enough for an 8K screenshot or a long stitched capture
+            max_pixels: 100_000_000,
+        }
+    }
+}
+
+pub struct ImageSandbox {
+    roots: Vec<PathBuf>,
+    // Roots ClipMind writes to itself, their files go through `blobs`
+    owned: Vec<PathBuf>,
+    max_file_bytes: u64,
+    max_pixels: u64,
+    blobs: Mutex<Blobs>,
+}
+
+impl ImageSandbox {
+    pub fn new(roots: Vec<PathBuf>, owned: Vec<PathBuf>, config: &SandboxConfig, blobs: Blobs) -> Self {
+        // Compare canonical forms, roots that don't exist can't contain anything
+        let canonical = |roots: &[PathBuf]| -> Vec<PathBuf> {
+            let mut roots: Vec<PathBuf> = roots.iter().filter_map(|root| fs::canonicalize(root).ok()).collect();
+            roots.sort();
+            roots.dedup();
+            roots
+        };
+        let owned = canonical(&owned);
+        let roots = canonical(&[roots, owned.clone(), config.extra_roots.clone()].concat());
+
+        ImageSandbox {
+            roots,
+            owned,
+            max_file_bytes: config.max_file_bytes,
+            max_pixels: config.max_pixels,
+            blobs: Mutex::new(blobs),
+        }
+    }
+
+    /// How owned files are read and written right now, see vault.rs
+    pub fn blobs(&self) -> Blobs {
+        self.blobs.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_blobs(&self, blobs: Blobs) {
+        *self.blobs.lock().unwrap_or_else(|e| e.into_inner()) = blobs;
+    }
+
+    /// Resolve `path` to a canonical file inside one of the roots
+    pub fn check(&self, path: &str) -> Result<PathBuf, ImageError> {
+        let raw = Path::new(path);
+        if raw.components().any(|c| c == Component::ParentDir) {
+            return Err(ImageError::Traversal(path.to_string()));
+        }
+
+        // Resolves symlinks, so a link inside a root pointing outside is caught below
+        let canonical = fs::canonicalize(raw).map_err(|_| ImageError::NotFound(path.to_string()))?;
+        if !self.roots.iter().any(|root| canonical.starts_with(root)) {
+            return Err(ImageError::OutsideAllowedRoots(path.to_string()));
+        }
+
+        let meta = fs::metadata(&canonical).map_err(|e| ImageError::Io(e.to_string()))?;
+        if !meta.is_file() {
+            return Err(ImageError::NotAFile(path.to_string()));
+        }
+        if meta.len() > self.max_file_bytes {
+            return Err(ImageError::TooLarge {
+                bytes: meta.len(),
+                limit: self.max_file_bytes,
+            });
+        }
+
+        Ok(canonical)
+    }
+
+    /// Decode an already checked path, refusing decompression bombs up front
+    pub fn decode(&self, path: &Path) -> Result<DynamicImage, ImageError> {
+        // Owned files can be sealed, those are decrypted into memory first
+        if self.owned.iter().any(|root| path.starts_with(root)) {
+            let bytes = self.read(path)?;
+            return self.decode_with(|| {
+                ImageReader::new(Cursor::new(&bytes[..]))
+                    .with_guessed_format()
+                    .map_err(|e| ImageError::Io(e.to_string()))
+            });
+        }
+
+        self.decode_with(|| {
+            ImageReader::open(path)
+                .map_err(|e| ImageError::Io(e.to_string()))?
+                .with_guessed_format()
+                .map_err(|e| ImageError::Io(e.to_string()))
+        })
+    }
+
+    fn decode_with<R: BufRead + Seek>(
+        &self,
+        reader: impl Fn() -> Result<ImageReader<R>, ImageError>,
+    ) -> Result<DynamicImage, ImageError> {
+        // Header only, tells us the size without allocating the pixels
+        let (width, height) = reader()?
+            .into_dimensions()
+            .map_err(|e| ImageError::Decode(e.to_string()))?;
+        if u64::from(width) * u64::from(height) > self.max_pixels {
+            return Err(ImageError::TooManyPixels {
+                width,
+                height,
+                limit: self.max_pixels,
+            });
+        }
+
+        // Backstop in case the header lied about the size
+        let mut limits = Limits::default();
+        limits.max_alloc = Some(self.max_pixels * 4 * 2);
+
+        let mut reader = reader()?;
+        reader.limits(limits);
+        reader.decode().map_err(|e| ImageError::Decode(e.to_string()))
+    }
+
+    /// Raw bytes of a file, decrypted when it's one ClipMind owns
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, ImageError> {
+        let result = if self.owned.iter().any(|root| path.starts_with(root)) {
+            self.blobs().read(path)
+        } else {
+            fs::read(path)
+        };
+        result.map_err(|e| ImageError::Io(e.to_string()))
+    }
+
+    pub fn open(&self, path: &str) -> Result<DynamicImage, ImageError> {
+        let canonical = self.check(path)?;
+        self.decode(&canonical)
+    }
+}
+
+/// Built-in roots plus the extra ones from the [images] settings
+pub fn build(app: &AppHandle) -> ImageSandbox {
+    let mut roots = Vec::new();
+
+    // Same folders screenshot_watcher.py looks in, plus whatever the Rust watcher is set to
+    if let Ok(home) = app.path().home_dir() {
+        roots.push(home.join("OneDrive").join("Pictures"));
+        roots.push(home.join("Pictures"));
+    }
+    if let Ok(pictures) = app.path().picture_dir() {
+        roots.push(pictures);
+    }
+    roots.extend(screenshot_watcher::roots(app).into_iter().map(|root| root.path));
+
+    // Folders ClipMind itself writes images to
+    let mut owned = Vec::new();
+    for dir in [storage::blob_dir(app), thumbnail::cache_dir(app)].into_iter().flatten() {
+        let _ = fs::create_dir_all(&dir);
+        owned.push(dir);
+    }
+
+    ImageSandbox::new(roots, owned, &settings::get(app).images, vault::blobs(app))
+}
@@ -0,0 +1,450 @@
+// Processed-file ledger for screenshots, replaces processed_screenshots.json
+// One row per file the watchers have looked at: its size and mtime when we saw it,
+// the content hash, the item it became and what happened. The OCR worker
+// (app/ingest/ocr_worker.py) marks rows "indexed" once it has run OCR and the
+// embeddings for their item
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use walkdir::WalkDir;
+
+use crate::backend;
+use crate::fingerprint::{self, IngestOutcome};
+use crate::screenshot_watcher;
+use crate::storage::{now_ts, Store};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS screenshot_ledger (
+    path VARCHAR NOT NULL PRIMARY KEY,
+    size INTEGER NOT NULL,
+    mtime INTEGER NOT NULL,
+    content_hash VARCHAR,
+    item_id INTEGER,
+    status VARCHAR NOT NULL,
+    error VARCHAR,
+    updated_ts INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ix_screenshot_ledger_status ON screenshot_ledger (status);
+";
+
+const COLUMNS: &str = "path, size, mtime, content_hash, item_id, status, error, updated_ts";
+
+// Python sets "indexed" itself, see finish() in ocr_worker.py
+const INDEXED: &str = "indexed";
+const INGESTED: &str = "ingested";
+const DUPLICATE: &str = "duplicate";
+const REJECTED: &str = "rejected";
+const FAILED: &str = "failed";
+const MISSING: &str = "missing";
+
+// Written by the old Python screenshot watcher, in the project root
+const LEGACY_CACHE: &str = "processed_screenshots.json";
+
+#[derive(Clone, Serialize)]
+pub struct LedgerEntry {
+    pub path: String,
+    pub size: u64,
+    /// Unix seconds
+    pub mtime: i64,
+    pub content_hash: Option<String>,
+    pub item_id: Option<i64>,
+    pub status: String,
+    pub error: Option<String>,
+    pub updated_ts: i64,
+}
+
+impl LedgerEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(LedgerEntry {
+            path: row.get("path")?,
+            size: row.get("size")?,
+            mtime: row.get("mtime")?,
+            content_hash: row.get("content_hash")?,
+            item_id: row.get("item_id")?,
+            status: row.get("status")?,
+            error: row.get("error")?,
+            updated_ts: row.get("updated_ts")?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct MovedItem {
+    pub item_id: i64,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Serialize)]
+pub struct ReconcileReport {
+    /// Screenshot items whose file was looked for
+    pub checked: usize,
+    pub moved: Vec<MovedItem>,
+    /// Items whose file is gone, they're kept but marked missing in the ledger
+    pub missing: Vec<i64>,
+    /// Ledger rows for deleted files that no item points at
+    pub pruned: usize,
+}
+
+/// Size and mtime (unix seconds) of `path`
+fn file_stamp(path: &Path) -> Option<(u64, i64)> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    Some((meta.len(), mtime))
+}
+
+pub fn get(store: &Store, path: &str) -> rusqlite::Result<Option<LedgerEntry>> {
+    store.with_conn(|conn| {
+        let sql = format!("SELECT {} FROM screenshot_ledger WHERE path = ?1", COLUMNS);
+        conn.query_row(&sql, [path], LedgerEntry::from_row).optional()
+    })
+}
+
+fn by_status(store: &Store, status: &str) -> rusqlite::Result<Vec<LedgerEntry>> {
+    store.with_conn(|conn| {
+        let sql = format!(
+            "SELECT {} FROM screenshot_ledger WHERE status = ?1 ORDER BY updated_ts DESC",
+            COLUMNS
+        );
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let rows = stmt.query_map([status], LedgerEntry::from_row)?;
+        rows.collect()
+    })
+}
+
+fn all(store: &Store) -> rusqlite::Result<Vec<LedgerEntry>> {
+    store.with_conn(|conn| {
+        let sql = format!("SELECT {} FROM screenshot_ledger", COLUMNS);
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let rows = stmt.query_map([], LedgerEntry::from_row)?;
+        rows.collect()
+    })
+}
+
+fn upsert(store: &Store, entry: &LedgerEntry) -> rusqlite::Result<()> {
+    store.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO screenshot_ledger (path, size, mtime, content_hash, item_id, status, error, updated_ts)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(path) DO UPDATE SET
+                size = excluded.size, mtime = excluded.mtime, content_hash = excluded.content_hash,
+                item_id = excluded.item_id, status = excluded.status, error = excluded.error,
+                updated_ts = excluded.updated_ts",
+            params![
+                entry.path,
+                entry.size,
+                entry.mtime,
+                entry.content_hash,
+                entry.item_id,
+                entry.status,
+                entry.error,
+                entry.updated_ts
+            ],
+        )?;
+        Ok(())
+    })
+}
+
+fn remove(store: &Store, path: &str) -> rusqlite::Result<()> {
+    store.with_conn(|conn| {
+        conn.execute("DELETE FROM screenshot_ledger WHERE path = ?1", [path])?;
+        Ok(())
+    })
+}
+
+/// True when `path` was handled before and hasn't changed since.
+/// Failed files don't count, those go through `retry_failed_screenshots`
+pub fn is_done(store: &Store, path: &Path) -> bool {
+    let Some((size, mtime)) = file_stamp(path) else {
+        return false;
+    };
+    match get(store, &path.to_string_lossy()) {
+        Ok(Some(entry)) => entry.status != FAILED && entry.size == size && entry.mtime == mtime,
+        _ => false,
+    }
+}
+
+/// Write down what happened to `path`. Paused captures aren't recorded, the file was never looked at
+pub fn record(store: &Store, path: &Path, content_hash: Option<&str>, outcome: &Result<IngestOutcome, String>) {
+    let (status, item_id, error) = match outcome {
+        Ok(IngestOutcome::Paused) => return,
+        Ok(IngestOutcome::Inserted { item_id }) => (INGESTED, Some(*item_id), None),
+        Ok(IngestOutcome::Duplicate { item_id }) => (DUPLICATE, Some(*item_id), None),
+        Ok(IngestOutcome::NearDuplicate { item_id, .. }) => (REJECTED, Some(*item_id), None),
+        Err(e) => (FAILED, None, Some(e.clone())),
+    };
+    let (size, mtime) = file_stamp(path).unwrap_or_default();
+
+    let entry = LedgerEntry {
+        path: path.to_string_lossy().into_owned(),
+        size,
+        mtime,
+        content_hash: content_hash.map(str::to_string),
+        item_id,
+        status: status.to_string(),
+        error,
+        updated_ts: now_ts(),
+    };
+    if let Err(e) = upsert(store, &entry) {
+        eprintln!("[LEDGER] Failed to record {}: {}", entry.path, e);
+    }
+}
+
+fn mark_missing(store: &Store, mut entry: LedgerEntry) -> rusqlite::Result<()> {
+    entry.status = MISSING.to_string();
+    entry.error = Some("File not found".to_string());
+    entry.updated_ts = now_ts();
+    upsert(store, &entry)
+}
+
+/// Image files under the watch roots, keyed by content hash.
+/// Only files with a size in `sizes` are hashed when it's given
+fn hash_watched_files(app: &AppHandle, sizes: Option<&HashSet<u64>>, skip: &HashSet<String>) -> HashMap<String, String> {
+    let mut found = HashMap::new();
+
+    for root in screenshot_watcher::roots(app) {
+        let walker = WalkDir::new(&root.path).max_depth(if root.recursive { usize::MAX } else { 1 });
+        for entry in walker.into_iter().filter_map(Result::ok) {
+            let path = entry.path();
+            if !entry.file_type().is_file() || !screenshot_watcher::is_image_path(path) {
+                continue;
+            }
+            let Ok(canonical) = fs::canonicalize(path) else {
+                continue;
+            };
+            let path_str = canonical.to_string_lossy().into_owned();
+            if skip.contains(&path_str) {
+                continue;
+            }
+            if let Some(sizes) = sizes {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                if !sizes.contains(&size) {
+                    continue;
+                }
+            }
+            if let Ok(hash) = fingerprint::file_hash(&canonical) {
+                found.entry(hash).or_insert(path_str);
+            }
+        }
+    }
+
+    found
+}
+
+fn reconcile(app: &AppHandle) -> Result<ReconcileReport, String> {
+    let store = app.state::<Store>();
+
+    let items: Vec<_> = store
+        .items_after_id(0)
+        .map_err(|e| format!("Failed to load items: {}", e))?
+        .into_iter()
+        .filter(fingerprint::is_image)
+        .collect();
+    let ledger: HashMap<String, LedgerEntry> = all(&store)
+        .map_err(|e| format!("Failed to load ledger: {}", e))?
+        .into_iter()
+        .map(|entry| (entry.path.clone(), entry))
+        .collect();
+
+    let referenced: HashSet<String> = items.iter().filter_map(|item| item.blob_uri.clone()).collect();
+    let lost: Vec<_> = items
+        .iter()
+        .filter(|item| item.blob_uri.as_deref().is_some_and(|p| !Path::new(p).exists()))
+        .collect();
+
+    // Sizes narrow down what has to be hashed, unless a lost file never made it into the ledger
+    let sizes: Option<HashSet<u64>> = lost
+        .iter()
+        .map(|item| ledger.get(item.blob_uri.as_deref()?).map(|entry| entry.size))
+        .collect();
+    let found = if lost.is_empty() {
+        HashMap::new()
+    } else {
+        hash_watched_files(app, sizes.as_ref(), &referenced)
+    };
+
+    let mut report = ReconcileReport {
+        checked: items.len(),
+        moved: Vec::new(),
+        missing: Vec::new(),
+        pruned: 0,
+    };
+
+    for item in lost {
+        let from = item.blob_uri.clone().unwrap_or_default();
+        let old_entry = ledger.get(&from).cloned();
+
+        match found.get(&item.content_hash) {
+            Some(to) => {
+                store
+                    .set_blob_uri(item.id, to)
+                    .map_err(|e| format!("Failed to update item {}: {}", item.id, e))?;
+
+                let (size, mtime) = file_stamp(Path::new(to)).unwrap_or_default();
+                let entry = LedgerEntry {
+                    path: to.clone(),
+                    size,
+                    mtime,
+                    content_hash: Some(item.content_hash.clone()),
+                    item_id: Some(item.id),
+                    status: old_entry.map(|e| e.status).unwrap_or_else(|| INGESTED.to_string()),
+                    error: None,
+                    updated_ts: now_ts(),
+                };
+                upsert(&store, &entry).map_err(|e| format!("Failed to update ledger: {}", e))?;
+                remove(&store, &from).map_err(|e| format!("Failed to update ledger: {}", e))?;
+
+                eprintln!("[LEDGER] Item {} moved: {} -> {}", item.id, from, to);
+                report.moved.push(MovedItem {
+                    item_id: item.id,
+                    from,
+                    to: to.clone(),
+                });
+            }
+            None => {
+                let entry = old_entry.unwrap_or_else(|| LedgerEntry {
+                    path: from.clone(),
+                    size: 0,
+                    mtime: 0,
+                    content_hash: Some(item.content_hash.clone()),
+                    item_id: Some(item.id),
+                    status: MISSING.to_string(),
+                    error: None,
+                    updated_ts: now_ts(),
+                });
+                mark_missing(&store, entry).map_err(|e| format!("Failed to update ledger: {}", e))?;
+                report.missing.push(item.id);
+            }
+        }
+    }
+
+    // Rows for deleted files nothing refers to, this is what kept the JSON file growing
+    for (path, _) in ledger.iter().filter(|(path, _)| !referenced.contains(*path)) {
+        if !Path::new(path).exists() {
+            remove(&store, path).map_err(|e| format!("Failed to prune ledger: {}", e))?;
+            report.pruned += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+/// One-time move of processed_screenshots.json into the ledger, like
+/// ProcessedFilesTracker._import_legacy_cache does when the Python watcher runs alone.
+/// Under the app only the OCR worker runs, which never touches the file
+fn import_legacy_cache(store: &Store) {
+    let Some(path) = backend::locate_project_root().map(|root| root.join(LEGACY_CACHE)) else {
+        return;
+    };
+    let Ok(raw) = fs::read_to_string(&path) else {
+        return;
+    };
+    let paths: Vec<String> = match serde_json::from_str(&raw) {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("[LEDGER] Can't read {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let mut imported = 0;
+    for file in paths {
+        // Files that are gone would only be pruned again later
+        let Some((size, mtime)) = file_stamp(Path::new(&file)) else {
+            continue;
+        };
+        if is_done(store, Path::new(&file)) {
+            continue;
+        }
+        let entry = LedgerEntry {
+            path: file,
+            size,
+            mtime,
+            content_hash: None,
+            item_id: None,
+            status: INDEXED.to_string(),
+            error: None,
+            updated_ts: now_ts(),
+        };
+        match upsert(store, &entry) {
+            Ok(()) => imported += 1,
+            // Keep the file around for the next start
+            Err(e) => {
+                eprintln!("[LEDGER] Failed to import {}: {}", path.display(), e);
+                return;
+            }
+        }
+    }
+
+    let mut migrated = path.clone().into_os_string();
+    migrated.push(".migrated");
+    if let Err(e) = fs::rename(&path, &migrated) {
+        eprintln!("[LEDGER] Failed to rename {}: {}", path.display(), e);
+    }
+    eprintln!("[LEDGER] Moved {} previously processed files into the ledger", imported);
+}
+
+/// Before the screenshot watcher starts, so the imported files aren't ingested again
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    let store = app.state::<Store>();
+    store
+        .with_conn(|conn| conn.execute_batch(SCHEMA))
+        .map_err(|e| format!("Failed to create ledger table: {}", e))?;
+    import_legacy_cache(&store);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_failed_screenshots(store: State<'_, Store>) -> Result<Vec<LedgerEntry>, String> {
+    by_status(&store, FAILED).map_err(|e| format!("Failed to load ledger: {}", e))
+}
+
+/// Run failed files through ingest again, all of them unless `paths` is given.
+/// Returns the updated ledger rows
+#[tauri::command]
+pub async fn retry_failed_screenshots(app: AppHandle, paths: Option<Vec<String>>) -> Result<Vec<LedgerEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = app.state::<Store>();
+        let failed = by_status(&store, FAILED).map_err(|e| format!("Failed to load ledger: {}", e))?;
+
+        let mut retried = Vec::new();
+        for entry in failed {
+            if paths.as_ref().is_some_and(|only| !only.contains(&entry.path)) {
+                continue;
+            }
+            let path = entry.path.clone();
+            if Path::new(&path).exists() {
+                // Records the new outcome itself
+                let _ = fingerprint::ingest(&app, &path);
+            } else {
+                mark_missing(&store, entry).map_err(|e| format!("Failed to update ledger: {}", e))?;
+            }
+            if let Some(updated) = get(&store, &path).map_err(|e| format!("Failed to load ledger: {}", e))? {
+                retried.push(updated);
+            }
+        }
+        Ok(retried)
+    })
+    .await
+    .map_err(|e| format!("Retry failed: {}", e))?
+}
+
+/// Point screenshot items at files that were moved inside the watch roots,
+/// flag the ones whose file is gone and prune rows for deleted files
+#[tauri::command]
+pub async fn reconcile_screenshots(app: AppHandle) -> Result<ReconcileReport, String> {
+    tauri::async_runtime::spawn_blocking(move || reconcile(&app))
+        .await
+        .map_err(|e| format!("Reconcile failed: {}", e))?
+}
@@ -1,34 +1,147 @@
-use std::fs;
-use image::ImageReader;
+use tauri::{AppHandle, Manager};
+
+mod archive;
+mod autopaste;
+mod backend;
+mod capture;
+mod clipboard_watcher;
+mod config;
+mod dedup;
+mod fingerprint;
+mod hotkeys;
+mod image_ipc;
+mod image_sandbox;
+mod ledger;
+mod native_clipboard;
+mod protocol;
+mod pins;
+mod quickboard;
+mod representations;
+mod retention;
+mod screenshot_watcher;
+mod search;
+mod sensitive;
+mod settings;
+mod storage;
+mod tags;
+mod thumbnail;
+mod tray;
+mod vault;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-#[tauri::command]
-fn read_image_file(path: String) -> Result<(Vec<u8>, u32, u32), String> {
-    // Read and decode the image
-    let img = ImageReader::open(&path)
-        .map_err(|e| format!("Failed to open image: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
-
-    // Convert to RGBA8
-    let rgba = img.to_rgba8();
-    let (width, height) = rgba.dimensions();
-
-    // Return RGBA bytes, width, and height
-    Ok((rgba.into_raw(), width, height))
+/// Everything that needs the history open. Runs during setup, or on the
+/// first unlock when the history is encrypted
+pub(crate) fn start_history(app: &AppHandle) -> Result<(), String> {
+    pins::start(app)?;
+    tags::start(app)?;
+    sensitive::start(app)?;
+    fingerprint::start(app)?;
+    ledger::start(app)?;
+    representations::start(app)?;
+    retention::start(app)?;
+    search::start(app)?;
+    screenshot_watcher::start(app)?;
+    clipboard_watcher::start(app.clone());
+    Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(hotkeys::plugin())
         .plugin(tauri_plugin_clipboard_manager::init())
-        .invoke_handler(tauri::generate_handler![greet, read_image_file])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .register_asynchronous_uri_scheme_protocol(protocol::SCHEME, protocol::handle)
+        .setup(|app| {
+            // Before anything that reads its settings
+            settings::start(app.handle())?;
+
+            // Manages the Store, left closed until `unlock` when the history is encrypted
+            let db_path = storage::locate_db(app.handle())?;
+            vault::start(app.handle(), &db_path)?;
+            app.manage(search::SearchIndex::default());
+            app.manage(capture::Capture::default());
+            app.manage(image_sandbox::build(app.handle()));
+
+            if !vault::is_locked(app.handle()) {
+                start_history(app.handle())?;
+            }
+            backend::start(app.handle());
+            hotkeys::start(app.handle());
+            autopaste::start(app.handle());
+            tray::start(app.handle())?;
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            vault::on_window_event(window, event);
+            tray::on_window_event(window, event);
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            image_ipc::read_image_file,
+            image_ipc::stream_image_file,
+            thumbnail::read_image_thumbnail,
+            storage::list_recent_items,
+            storage::get_item,
+            storage::delete_item,
+            search::search_items,
+            search::tag_search_results,
+            tags::list_tags,
+            tags::item_tags,
+            tags::tag_items,
+            tags::untag_items,
+            tags::rename_tag,
+            tags::delete_tag,
+            tags::list_collections,
+            tags::create_collection,
+            tags::delete_collection,
+            tags::add_to_collection,
+            tags::remove_from_collection,
+            tags::collection_items,
+            sensitive::scan_text,
+            dedup::find_duplicates,
+            dedup::dedupe_history,
+            retention::apply_retention,
+            archive::export_history,
+            archive::import_history,
+            fingerprint::ingest_screenshot,
+            ledger::list_failed_screenshots,
+            ledger::retry_failed_screenshots,
+            ledger::reconcile_screenshots,
+            representations::item_formats,
+            representations::copy_item,
+            pins::list_pinned,
+            pins::pin_item,
+            pins::unpin_item,
+            pins::reorder_pins,
+            autopaste::autopaste_settings,
+            autopaste::set_autopaste,
+            vault::vault_status,
+            vault::unlock,
+            vault::lock_history,
+            vault::touch_vault,
+            vault::encrypt_history,
+            settings::get_settings,
+            settings::update_settings,
+            backend::backend_status,
+            backend::backend_restart,
+            capture::pause_capture,
+            capture::resume_capture,
+            capture::capture_state,
+            hotkeys::list_hotkeys,
+            hotkeys::set_hotkey,
+            quickboard::toggle_quickboard
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Don't leave orphaned Python processes behind
+                app.state::<backend::Backend>().shutdown();
+            }
+        });
+}
@@ -0,0 +1,447 @@
+// Multi-format clipboard access, straight to each platform's clipboard.
+// arboard and the clipboard plugin set one format per call (each replacing the last)
+// and don't know RTF, so an item with HTML, RTF and plain text couldn't be put back
+// the way it was copied. `write` offers every representation in one transaction.
+// Linux: Wayland through the data-control protocol, otherwise X11 (XWayland too)
+// by owning the CLIPBOARD selection from a background thread.
+// Windows: clipboard-win. macOS: NSPasteboard
+
+use crate::representations::Representation;
+
+/// Replace the clipboard with all of `reps` at once
+pub fn write(reps: &[Representation]) -> Result<(), String> {
+    if reps.is_empty() {
+        return Err("Nothing to copy".to_string());
+    }
+    platform::write(reps)
+}
+
+/// The clipboard's raw bytes in `format`, None when it isn't offered in that format.
+/// Meant for formats that are plain bytes everywhere (RTF)
+pub fn read(format: &str) -> Option<Vec<u8>> {
+    platform::read(format).filter(|data| !data.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::representations::{parse_uri_list, uri_list, Representation, HTML, RTF, TEXT, URI_LIST};
+
+    const GNOME_FILES: &str = "x-special/gnome-copied-files";
+
+    /// Names a format goes by on X11/Wayland, the first is the one read
+    fn targets(format: &str) -> Vec<&str> {
+        match format {
+            TEXT => vec!["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"],
+            RTF => vec![RTF, "application/rtf", "text/richtext"],
+            // Nautilus and friends only paste files from their own target
+            URI_LIST => vec![URI_LIST, GNOME_FILES],
+            HTML => vec![HTML],
+            other => vec![other],
+        }
+    }
+
+    /// Every (target, data) pair to offer for `reps`
+    fn offers(reps: &[Representation]) -> Vec<(&str, Vec<u8>)> {
+        let mut offers = Vec::new();
+        for rep in reps {
+            for target in targets(&rep.format) {
+                let data = if target == GNOME_FILES {
+                    let uris = uri_list(&parse_uri_list(&rep.text())).replace("\r\n", "\n");
+                    format!("copy\n{}", uris).into_bytes()
+                } else {
+                    rep.data.clone()
+                };
+                offers.push((target, data));
+            }
+        }
+        offers
+    }
+
+    fn wayland() -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+    }
+
+    pub fn write(reps: &[Representation]) -> Result<(), String> {
+        let offers = offers(reps);
+        if wayland() {
+            // GNOME has no data-control, XWayland's selection reaches Wayland apps too
+            match wayland::write(&offers) {
+                Ok(()) => return Ok(()),
+                Err(e) => eprintln!("[CLIPBOARD] {}, using X11", e),
+            }
+        }
+        x11::write(offers)
+    }
+
+    pub fn read(format: &str) -> Option<Vec<u8>> {
+        let targets = targets(format);
+        if wayland() {
+            match wayland::read(&targets) {
+                Ok(data) => return data,
+                Err(e) => eprintln!("[CLIPBOARD] {}, using X11", e),
+            }
+        }
+        x11::read(&targets)
+            .map_err(|e| eprintln!("[CLIPBOARD] {}", e))
+            .ok()
+            .flatten()
+    }
+
+    mod wayland {
+        use std::io::Read;
+
+        use wl_clipboard_rs::copy::{MimeSource, MimeType, Options, Source};
+        use wl_clipboard_rs::paste::{self, ClipboardType, Seat};
+
+        fn err(e: impl std::fmt::Display) -> String {
+            format!("Wayland: {}", e)
+        }
+
+        pub fn write(offers: &[(&str, Vec<u8>)]) -> Result<(), String> {
+            let sources = offers
+                .iter()
+                .map(|(target, data)| MimeSource {
+                    source: Source::Bytes(data.clone().into_boxed_slice()),
+                    mime_type: MimeType::Specific(target.to_string()),
+                })
+                .collect();
+            let mut options = Options::new();
+            // The text targets are all listed, otherwise it may serve the HTML as plain text
+            options.omit_additional_text_mime_types(true);
+            // Serves pastes from its own thread until something else is copied
+            options.copy_multi(sources).map_err(err)
+        }
+
+        /// Ok(None) when nothing is offered as any of `targets`
+        pub fn read(targets: &[&str]) -> Result<Option<Vec<u8>>, String> {
+            for target in targets {
+                match paste::get_contents(ClipboardType::Regular, Seat::Unspecified, paste::MimeType::Specific(target)) {
+                    Ok((mut pipe, _)) => {
+                        let mut data = Vec::new();
+                        pipe.read_to_end(&mut data).map_err(err)?;
+                        return Ok(Some(data));
+                    }
+                    Err(paste::Error::NoSeats | paste::Error::ClipboardEmpty | paste::Error::NoMimeType) => {}
+                    Err(e) => return Err(err(e)),
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    mod x11 {
+        use std::thread;
+        use std::time::{Duration, Instant};
+
+        use x11rb::connection::{Connection, RequestConnection};
+        use x11rb::protocol::xproto::{
+            Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, PropMode, SelectionNotifyEvent,
+            SelectionRequestEvent, Window, WindowClass, SELECTION_NOTIFY_EVENT,
+        };
+        use x11rb::protocol::Event;
+        use x11rb::rust_connection::RustConnection;
+        use x11rb::wrapper::ConnectionExt as _;
+        use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME, NONE};
+
+        /// How long the clipboard owner gets to answer a read
+        const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+        fn err(e: impl std::fmt::Display) -> String {
+            format!("X11: {}", e)
+        }
+
+        /// Unmapped window to own the selection with, or receive it on
+        fn open() -> Result<(RustConnection, Window), String> {
+            let (conn, screen) = x11rb::connect(None).map_err(err)?;
+            let root = conn.setup().roots[screen].root;
+            let window = conn.generate_id().map_err(err)?;
+            conn.create_window(
+                COPY_DEPTH_FROM_PARENT,
+                window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_ONLY,
+                0,
+                &CreateWindowAux::new(),
+            )
+            .map_err(err)?;
+            Ok((conn, window))
+        }
+
+        fn atom(conn: &RustConnection, name: &str) -> Result<Atom, String> {
+            Ok(conn
+                .intern_atom(false, name.as_bytes())
+                .map_err(err)?
+                .reply()
+                .map_err(err)?
+                .atom)
+        }
+
+        pub fn write(offers: Vec<(&str, Vec<u8>)>) -> Result<(), String> {
+            let (conn, window) = open()?;
+            let clipboard = atom(&conn, "CLIPBOARD")?;
+            let targets = atom(&conn, "TARGETS")?;
+            let offers = offers
+                .into_iter()
+                .map(|(target, data)| Ok((atom(&conn, target)?, data)))
+                .collect::<Result<Vec<_>, String>>()?;
+
+            conn.set_selection_owner(window, clipboard, CURRENT_TIME).map_err(err)?;
+            let owner = conn
+                .get_selection_owner(clipboard)
+                .map_err(err)?
+                .reply()
+                .map_err(err)?
+                .owner;
+            if owner != window {
+                return Err("X11: couldn't take over the clipboard".to_string());
+            }
+
+            // The data lives as long as this thread, it ends once something else is copied
+            thread::spawn(move || {
+                if let Err(e) = serve(&conn, clipboard, targets, &offers) {
+                    eprintln!("[CLIPBOARD] {}", e);
+                }
+            });
+            Ok(())
+        }
+
+        fn serve(conn: &RustConnection, clipboard: Atom, targets: Atom, offers: &[(Atom, Vec<u8>)]) -> Result<(), String> {
+            loop {
+                match conn.wait_for_event().map_err(err)? {
+                    Event::SelectionRequest(request) if request.selection == clipboard => {
+                        answer(conn, &request, targets, offers)?
+                    }
+                    Event::SelectionClear(clear) if clear.selection == clipboard => return Ok(()),
+                    _ => {}
+                }
+            }
+        }
+
+        fn answer(
+            conn: &RustConnection,
+            request: &SelectionRequestEvent,
+            targets: Atom,
+            offers: &[(Atom, Vec<u8>)],
+        ) -> Result<(), String> {
+            // Old clients leave the property out and expect the target to be used
+            let property = if request.property == NONE {
+                request.target
+            } else {
+                request.property
+            };
+            // Anything bigger than one request would need INCR transfers, it's refused instead
+            let max = conn.maximum_request_bytes().saturating_sub(64);
+
+            let served = if request.target == targets {
+                let mut list: Vec<Atom> = offers.iter().map(|(target, _)| *target).collect();
+                list.push(targets);
+                conn.change_property32(PropMode::REPLACE, request.requestor, property, AtomEnum::ATOM, &list)
+                    .map_err(err)?;
+                true
+            } else {
+                match offers.iter().find(|(target, _)| *target == request.target) {
+                    Some((target, data)) if data.len() <= max => {
+                        conn.change_property8(PropMode::REPLACE, request.requestor, property, *target, data)
+                            .map_err(err)?;
+                        true
+                    }
+                    _ => false,
+                }
+            };
+
+            let notify = SelectionNotifyEvent {
+                response_type: SELECTION_NOTIFY_EVENT,
+                sequence: 0,
+                time: request.time,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                property: if served { property } else { NONE },
+            };
+            conn.send_event(false, request.requestor, EventMask::NO_EVENT, notify)
+                .map_err(err)?;
+            conn.flush().map_err(err)
+        }
+
+        pub fn read(targets: &[&str]) -> Result<Option<Vec<u8>>, String> {
+            let (conn, window) = open()?;
+            let clipboard = atom(&conn, "CLIPBOARD")?;
+            let property = atom(&conn, "CLIPMIND_SELECTION")?;
+            let incr = atom(&conn, "INCR")?;
+
+            for name in targets {
+                let target = atom(&conn, name)?;
+                conn.convert_selection(window, clipboard, target, property, CURRENT_TIME)
+                    .map_err(err)?;
+                conn.flush().map_err(err)?;
+                if !wait_for_notify(&conn, window)? {
+                    continue;
+                }
+
+                let reply = conn
+                    .get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX)
+                    .map_err(err)?
+                    .reply()
+                    .map_err(err)?;
+                // Huge selections come in INCR chunks, not worth it for rich text
+                if reply.type_ != incr && !reply.value.is_empty() {
+                    return Ok(Some(reply.value));
+                }
+            }
+            Ok(None)
+        }
+
+        /// Wait for the owner's answer, true when it converted the selection
+        fn wait_for_notify(conn: &RustConnection, window: Window) -> Result<bool, String> {
+            let deadline = Instant::now() + READ_TIMEOUT;
+            while Instant::now() < deadline {
+                match conn.poll_for_event().map_err(err)? {
+                    Some(Event::SelectionNotify(notify)) if notify.requestor == window => {
+                        return Ok(notify.property != NONE)
+                    }
+                    Some(_) => {}
+                    None => thread::sleep(Duration::from_millis(5)),
+                }
+            }
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::io::Cursor;
+
+    use clipboard_win::options::NoClear;
+    use clipboard_win::{raw, register_format, Clipboard};
+    use image::ImageFormat;
+
+    use crate::representations::{parse_uri_list, Representation, HTML, PNG, RTF, TEXT, URI_LIST};
+
+    fn err(e: impl std::fmt::Display) -> String {
+        format!("Clipboard: {}", e)
+    }
+
+    /// Registered clipboard format for a MIME type
+    fn format_id(format: &str) -> Result<u32, String> {
+        let name = match format {
+            HTML => "HTML Format",
+            RTF => "Rich Text Format",
+            PNG => "PNG",
+            other => other,
+        };
+        register_format(name)
+            .map(|id| id.get())
+            .ok_or_else(|| format!("Clipboard: can't register format {}", name))
+    }
+
+    /// Apps that don't know PNG still take a bitmap
+    fn bitmap(png: &[u8]) -> Result<Vec<u8>, String> {
+        let img = image::load_from_memory_with_format(png, ImageFormat::Png)
+            .map_err(|e| format!("Failed to decode stored image: {}", e))?
+            .to_rgb8();
+        let mut bmp = Vec::new();
+        img.write_to(&mut Cursor::new(&mut bmp), ImageFormat::Bmp)
+            .map_err(|e| format!("Failed to encode bitmap: {}", e))?;
+        Ok(bmp)
+    }
+
+    fn set(rep: &Representation) -> Result<(), String> {
+        match rep.format.as_str() {
+            TEXT => raw::set_string_with(&rep.text(), NoClear).map_err(err),
+            HTML => raw::set_html_with(format_id(HTML)?, &rep.text(), NoClear).map_err(err),
+            URI_LIST => {
+                let paths: Vec<String> = parse_uri_list(&rep.text())
+                    .iter()
+                    .map(|p| p.to_string_lossy().replace('/', "\\"))
+                    .collect();
+                raw::set_file_list_with(&paths, NoClear).map_err(err)
+            }
+            PNG => {
+                raw::set_without_clear(format_id(PNG)?, &rep.data).map_err(err)?;
+                raw::set_bitmap_with(&bitmap(&rep.data)?, NoClear).map_err(err)
+            }
+            other => raw::set_without_clear(format_id(other)?, &rep.data).map_err(err),
+        }
+    }
+
+    pub fn write(reps: &[Representation]) -> Result<(), String> {
+        // Open until dropped, everything below is one transaction
+        let _clipboard = Clipboard::new_attempts(10).map_err(err)?;
+        raw::empty().map_err(err)?;
+        reps.iter().try_for_each(set)
+    }
+
+    pub fn read(format: &str) -> Option<Vec<u8>> {
+        let id = format_id(format).ok()?;
+        let _clipboard = Clipboard::new_attempts(10).ok()?;
+        let mut data = Vec::new();
+        raw::get_vec(id, &mut data).ok()?;
+        Some(data)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::runtime::ProtocolObject;
+    use objc2_app_kit::{
+        NSPasteboard, NSPasteboardType, NSPasteboardTypeHTML, NSPasteboardTypePNG, NSPasteboardTypeRTF,
+        NSPasteboardTypeString,
+    };
+    use objc2_foundation::{NSArray, NSData, NSString, NSURL};
+
+    use crate::representations::{parse_uri_list, Representation, HTML, PNG, RTF, TEXT, URI_LIST};
+
+    fn pasteboard_type(format: &str) -> Option<&'static NSPasteboardType> {
+        // SAFETY: AppKit constants, set for the whole run
+        unsafe {
+            match format {
+                TEXT => Some(NSPasteboardTypeString),
+                HTML => Some(NSPasteboardTypeHTML),
+                RTF => Some(NSPasteboardTypeRTF),
+                PNG => Some(NSPasteboardTypePNG),
+                _ => None,
+            }
+        }
+    }
+
+    pub fn write(reps: &[Representation]) -> Result<(), String> {
+        let pasteboard = NSPasteboard::generalPasteboard();
+        pasteboard.clearContents();
+
+        // One pasteboard item per file, the other formats are added to the first
+        if let Some(files) = reps.iter().find(|rep| rep.format == URI_LIST) {
+            let urls: Vec<_> = parse_uri_list(&files.text())
+                .iter()
+                .map(|path| {
+                    ProtocolObject::from_retained(NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy())))
+                })
+                .collect();
+            if !pasteboard.writeObjects(&NSArray::from_retained_slice(&urls)) {
+                return Err("Failed to copy files".to_string());
+            }
+        }
+
+        for rep in reps {
+            let Some(kind) = pasteboard_type(&rep.format) else {
+                continue;
+            };
+            if !pasteboard.setData_forType(Some(&NSData::with_bytes(&rep.data)), kind) {
+                return Err(format!("Failed to copy {}", rep.format));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(format: &str) -> Option<Vec<u8>> {
+        let kind = pasteboard_type(format)?;
+        NSPasteboard::generalPasteboard()
+            .dataForType(kind)
+            .map(|data| data.to_vec())
+    }
+}
@@ -0,0 +1,189 @@
+// Pinned items, shown above the recent list in the quickboard in an order the user picks.
+// Pins live in their own table so the Python side's `item` schema stays untouched.
+// Anything that prunes history on its own (dedupe_history, the expiry sweep, retention) skips
+// pinned items, only an explicit delete_item removes them
+
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::storage::{now_ts, Item, Store, ITEM_COLUMNS};
+
+pub const CHANGED_EVENT: &str = "pins://changed";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item_pin (
+    item_id INTEGER NOT NULL PRIMARY KEY,
+    position INTEGER NOT NULL,
+    pinned_ts INTEGER NOT NULL
+);
+";
+
+/// WHERE clause for passes that delete items on their own
+pub const NOT_PINNED: &str = "id NOT IN (SELECT item_id FROM item_pin)";
+
+#[derive(Clone, Serialize)]
+pub struct PinnedItem {
+    #[serde(flatten)]
+    pub item: Item,
+    /// 0 is the top of the list
+    pub position: u32,
+    pub pinned_ts: i64,
+}
+
+/// Pinned ids, top first. Pins of deleted items are dropped on the way
+fn order(conn: &Connection) -> rusqlite::Result<Vec<i64>> {
+    conn.execute("DELETE FROM item_pin WHERE item_id NOT IN (SELECT id FROM item)", [])?;
+    let mut stmt = conn.prepare_cached("SELECT item_id FROM item_pin ORDER BY position, pinned_ts")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Renumber positions to match `ids`
+fn write_order(conn: &Connection, ids: &[i64]) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached("UPDATE item_pin SET position = ?2 WHERE item_id = ?1")?;
+    for (position, id) in ids.iter().enumerate() {
+        stmt.execute(params![id, position as i64])?;
+    }
+    Ok(())
+}
+
+pub fn pinned_ids(store: &Store) -> rusqlite::Result<HashSet<i64>> {
+    store.with_conn(|conn| {
+        let mut stmt = conn.prepare_cached("SELECT item_id FROM item_pin")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    })
+}
+
+pub fn list(store: &Store, source: Option<&str>) -> rusqlite::Result<Vec<PinnedItem>> {
+    store.with_conn(|conn| {
+        let sql = format!(
+            "SELECT {}, p.position, p.pinned_ts FROM item_pin p JOIN item ON item.id = p.item_id
+             WHERE ?1 IS NULL OR source = ?1
+             ORDER BY p.position, p.pinned_ts",
+            ITEM_COLUMNS
+        );
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let rows = stmt.query_map([source], |row| {
+            Ok(PinnedItem {
+                item: Item::from_row(row)?,
+                position: row.get("position")?,
+                pinned_ts: row.get("pinned_ts")?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+/// Pin `id` at `position` (the end when None), or move it there when it's already pinned
+pub fn pin(store: &Store, id: i64, position: Option<usize>) -> Result<Vec<i64>, String> {
+    store
+        .get(id)
+        .map_err(|e| format!("Failed to load item: {}", e))?
+        .ok_or_else(|| format!("Item {} not found", id))?;
+
+    store
+        .with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let mut ids = order(&tx)?;
+            ids.retain(|&pinned| pinned != id);
+            let position = position.unwrap_or(ids.len()).min(ids.len());
+            ids.insert(position, id);
+
+            tx.execute(
+                "INSERT OR IGNORE INTO item_pin (item_id, position, pinned_ts) VALUES (?1, ?2, ?3)",
+                params![id, position as i64, now_ts()],
+            )?;
+            write_order(&tx, &ids)?;
+            tx.commit()?;
+            Ok(ids)
+        })
+        .map_err(|e| format!("Failed to pin item: {}", e))
+}
+
+/// The remaining pins, None when `id` wasn't pinned
+pub fn unpin(store: &Store, id: i64) -> Result<Option<Vec<i64>>, String> {
+    store
+        .with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            if tx.execute("DELETE FROM item_pin WHERE item_id = ?1", [id])? == 0 {
+                return Ok(None);
+            }
+            let ids = order(&tx)?;
+            write_order(&tx, &ids)?;
+            tx.commit()?;
+            Ok(Some(ids))
+        })
+        .map_err(|e| format!("Failed to unpin item: {}", e))
+}
+
+/// Put `ids` at the top in the given order, pins not listed keep their order below them
+pub fn reorder(store: &Store, ids: &[i64]) -> Result<Vec<i64>, String> {
+    let mut seen = HashSet::new();
+    if let Some(dup) = ids.iter().find(|id| !seen.insert(**id)) {
+        return Err(format!("Item {} is listed twice", dup));
+    }
+
+    store
+        .with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let current = order(&tx)?;
+            if let Some(missing) = ids.iter().find(|id| !current.contains(id)) {
+                return Ok(Err(format!("Item {} isn't pinned", missing)));
+            }
+
+            let mut new: Vec<i64> = ids.to_vec();
+            new.extend(current.into_iter().filter(|id| !seen.contains(id)));
+            write_order(&tx, &new)?;
+            tx.commit()?;
+            Ok(Ok(new))
+        })
+        .map_err(|e| format!("Failed to reorder pins: {}", e))?
+}
+
+fn changed(app: &AppHandle, ids: &[i64]) {
+    if let Err(e) = app.emit(CHANGED_EVENT, ids) {
+        eprintln!("[PINS] Failed to emit change: {}", e);
+    }
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    app.state::<Store>()
+        .with_conn(|conn| {
+            conn.execute_batch(SCHEMA)?;
+            let ids = order(conn)?;
+            write_order(conn, &ids)
+        })
+        .map_err(|e| format!("Failed to create pin table: {}", e))
+}
+
+/// Pinned items, top first, optionally only those from `source`
+#[tauri::command]
+pub async fn list_pinned(store: State<'_, Store>, source: Option<String>) -> Result<Vec<PinnedItem>, String> {
+    list(&store, source.as_deref()).map_err(|e| format!("Failed to list pinned items: {}", e))
+}
+
+/// Pin item `id`, at the end unless `position` says otherwise. Returns the pinned ids in order
+#[tauri::command]
+pub async fn pin_item(app: AppHandle, store: State<'_, Store>, id: i64, position: Option<usize>) -> Result<Vec<i64>, String> {
+    let ids = pin(&store, id, position)?;
+    changed(&app, &ids);
+    Ok(ids)
+}
+
+#[tauri::command]
+pub async fn unpin_item(app: AppHandle, store: State<'_, Store>, id: i64) -> Result<Vec<i64>, String> {
+    let ids = unpin(&store, id)?.ok_or_else(|| format!("Item {} isn't pinned", id))?;
+    changed(&app, &ids);
+    Ok(ids)
+}
+
+#[tauri::command]
+pub async fn reorder_pins(app: AppHandle, store: State<'_, Store>, ids: Vec<i64>) -> Result<Vec<i64>, String> {
+    let ids = reorder(&store, &ids)?;
+    changed(&app, &ids);
+    Ok(ids)
+}
@@ -0,0 +1,309 @@
+// clipmind:// protocol, serves item images and OCR text straight from Rust
+// so previews don't need the Python server
+//
+//   clipmind://item/{id}/image          original screenshot
+//   clipmind://item/{id}/thumb?w=&h=    PNG thumbnail (h defaults to w)
+//   clipmind://item/{id}/ocr            OCR text
+//
+// Windows webviews see these as http://clipmind.localhost/item/..., and
+// clipmind://localhost/item/... works too, so the host is only used when it
+// is the first path segment
+
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use image::ImageFormat;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, UriSchemeContext, UriSchemeResponder, Wry};
+use xxhash_rust::xxh64::xxh64;
+
+use crate::image_sandbox::{ImageError, ImageSandbox};
+use crate::storage::{Item, Store};
+use crate::thumbnail;
+
+pub const SCHEME: &str = "clipmind";
+
+// Same size the quickboard list shows, doubled for HiDPI
+const DEFAULT_THUMB_WIDTH: u32 = 160;
+
+// Items never change once written, but the screenshot file behind them can
+const CACHE_CONTROL: &str = "private, max-age=3600, must-revalidate";
+
+enum Route {
+    Image(i64),
+    Thumb { id: i64, w: u32, h: u32 },
+    Ocr(i64),
+}
+
+struct Body {
+    bytes: Vec<u8>,
+    content_type: String,
+    etag: String,
+}
+
+struct Failure {
+    status: StatusCode,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Failure {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ImageError> for Failure {
+    fn from(e: ImageError) -> Self {
+        let status = match e {
+            ImageError::NotFound(_) => StatusCode::NOT_FOUND,
+            ImageError::Traversal(_) | ImageError::OutsideAllowedRoots(_) | ImageError::NotAFile(_) => {
+                StatusCode::FORBIDDEN
+            }
+            ImageError::TooLarge { .. } | ImageError::TooManyPixels { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ImageError::Decode(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ImageError::Encode(_) | ImageError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Failure::new(status, e.to_string())
+    }
+}
+
+/// Entry point for register_asynchronous_uri_scheme_protocol
+pub fn handle(ctx: UriSchemeContext<'_, Wry>, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let app = ctx.app_handle().clone();
+
+    // Reading and resizing images blocks, keep it off the webview thread
+    tauri::async_runtime::spawn_blocking(move || {
+        responder.respond(respond(&app, &request));
+    });
+}
+
+fn respond(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return error_response(Failure::new(StatusCode::METHOD_NOT_ALLOWED, "Only GET and HEAD are supported"));
+    }
+
+    let body = parse_route(request).and_then(|route| load(app, route));
+    let response = match body {
+        Ok(body) => body_response(request, body),
+        Err(failure) => error_response(failure),
+    };
+
+    if request.method() == Method::HEAD {
+        let (parts, _) = response.into_parts();
+        return Response::from_parts(parts, Vec::new());
+    }
+    response
+}
+
+fn parse_route(request: &Request<Vec<u8>>) -> Result<Route, Failure> {
+    let uri = request.uri();
+
+    let mut segments: Vec<&str> = Vec::new();
+    if uri.host() == Some("item") {
+        segments.push("item");
+    }
+    segments.extend(uri.path().split('/').filter(|s| !s.is_empty()));
+
+    let not_found = || Failure::new(StatusCode::NOT_FOUND, format!("No route for {}", uri));
+
+    let ["item", id, kind] = segments.as_slice() else {
+        return Err(not_found());
+    };
+    let id: i64 = id
+        .parse()
+        .map_err(|_| Failure::new(StatusCode::BAD_REQUEST, format!("Invalid item id: {}", id)))?;
+
+    match *kind {
+        "image" => Ok(Route::Image(id)),
+        "ocr" => Ok(Route::Ocr(id)),
+        "thumb" => {
+            let w = query_param(uri.query(), "w")?.unwrap_or(DEFAULT_THUMB_WIDTH);
+            let h = query_param(uri.query(), "h")?.unwrap_or(w);
+            Ok(Route::Thumb { id, w, h })
+        }
+        _ => Err(not_found()),
+    }
+}
+
+fn query_param(query: Option<&str>, name: &str) -> Result<Option<u32>, Failure> {
+    let Some(query) = query else {
+        return Ok(None);
+    };
+
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        if key == name {
+            return value
+                .parse()
+                .map(Some)
+                .map_err(|_| Failure::new(StatusCode::BAD_REQUEST, format!("Invalid {}: {}", name, value)));
+        }
+    }
+    Ok(None)
+}
+
+/// The item behind a route, only screenshots have images and OCR text
+fn screenshot(app: &AppHandle, id: i64) -> Result<(Item, String), Failure> {
+    let item = app
+        .state::<Store>()
+        .get(id)
+        .map_err(|e| Failure::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load item: {}", e)))?
+        .ok_or_else(|| Failure::new(StatusCode::NOT_FOUND, format!("Item {} not found", id)))?;
+
+    match item.blob_uri.clone() {
+        Some(path) if item.source == "screenshot" => Ok((item, path)),
+        _ => Err(Failure::new(StatusCode::NOT_FOUND, format!("Item {} is not a screenshot", id))),
+    }
+}
+
+/// Changes whenever the file is replaced or edited
+fn file_tag(path: &Path, extra: &str) -> Result<String, Failure> {
+    let meta = fs::metadata(path).map_err(|e| Failure::from(ImageError::Io(e.to_string())))?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let key = format!("{}|{}|{}|{}", path.display(), modified, meta.len(), extra);
+    Ok(format!("\"{:016x}\"", xxh64(key.as_bytes(), 0)))
+}
+
+fn load(app: &AppHandle, route: Route) -> Result<Body, Failure> {
+    let sandbox = app.state::<ImageSandbox>();
+
+    match route {
+        Route::Image(id) => {
+            let (_, path) = screenshot(app, id)?;
+            let path = sandbox.check(&path)?;
+            let etag = file_tag(&path, "image")?;
+            let bytes = sandbox.read(&path)?;
+
+            let content_type = ImageFormat::from_path(&path)
+                .map(|f| f.to_mime_type())
+                .unwrap_or("application/octet-stream");
+
+            Ok(Body {
+                bytes,
+                content_type: content_type.to_string(),
+                etag,
+            })
+        }
+        Route::Thumb { id, w, h } => {
+            let (_, path) = screenshot(app, id)?;
+            let checked = sandbox.check(&path)?;
+            let etag = file_tag(&checked, &format!("thumb|{}x{}", w, h))?;
+
+            let cache_dir = thumbnail::cache_dir(app).map_err(|e| Failure::from(ImageError::Io(e)))?;
+            let bytes = thumbnail::thumbnail_png(&sandbox, &cache_dir, &path, w, h)?;
+
+            Ok(Body {
+                bytes,
+                content_type: "image/png".to_string(),
+                etag,
+            })
+        }
+        Route::Ocr(id) => {
+            let (item, _) = screenshot(app, id)?;
+            let etag = format!("\"{:016x}\"", xxh64(item.text.as_bytes(), 0));
+
+            Ok(Body {
+                bytes: item.text.into_bytes(),
+                content_type: "text/plain; charset=utf-8".to_string(),
+                etag,
+            })
+        }
+    }
+}
+
+/// Single `bytes=` range against a body of `len` bytes, as an inclusive span.
+/// None means the header should be ignored, Err means it can't be satisfied
+fn parse_range(header: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    // Multipart ranges aren't worth it for images, serve the whole thing
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+
+    let span = match (start.trim(), end.trim()) {
+        ("", "") => return None,
+        // Suffix range: the last N bytes
+        ("", suffix) => {
+            let n: u64 = suffix.parse().ok()?;
+            if n == 0 || len == 0 {
+                return Some(Err(()));
+            }
+            (len.saturating_sub(n), len - 1)
+        }
+        (start, end) => {
+            let start: u64 = start.parse().ok()?;
+            let end: u64 = if end.is_empty() { len.saturating_sub(1) } else { end.parse().ok()? };
+            if start >= len || end < start {
+                return Some(Err(()));
+            }
+            (start, end.min(len - 1))
+        }
+    };
+
+    Some(Ok(span))
+}
+
+fn body_response(request: &Request<Vec<u8>>, body: Body) -> Response<Vec<u8>> {
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, &body.content_type)
+        .header(header::ETAG, &body.etag)
+        .header(header::CACHE_CONTROL, CACHE_CONTROL)
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    let not_modified = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == body.etag || tag.trim() == "*"));
+    if not_modified {
+        return builder.status(StatusCode::NOT_MODIFIED).body(Vec::new()).unwrap();
+    }
+
+    let len = body.bytes.len() as u64;
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_range(v, len));
+
+    match range {
+        None => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, len)
+            .body(body.bytes)
+            .unwrap(),
+        Some(Ok((start, end))) => {
+            let slice = body.bytes[start as usize..=end as usize].to_vec();
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len))
+                .header(header::CONTENT_LENGTH, slice.len())
+                .body(slice)
+                .unwrap()
+        }
+        Some(Err(())) => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Vec::new())
+            .unwrap(),
+    }
+}
+
+fn error_response(failure: Failure) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(failure.status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(failure.message.into_bytes())
+        .unwrap()
+}
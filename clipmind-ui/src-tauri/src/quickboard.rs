@@ -0,0 +1,48 @@
+// Showing and hiding the quickboard window from the Rust side,
+// so it works without the main webview being alive
+
+use tauri::{AppHandle, Manager};
+
+use crate::autopaste;
+
+pub const LABEL: &str = "quickboard";
+
+pub fn show(app: &AppHandle) {
+    let Some(qb) = app.get_webview_window(LABEL) else {
+        eprintln!("[QB] quickboard window missing");
+        return;
+    };
+
+    // Whatever had focus before we take it is where auto-paste goes
+    if !qb.is_visible().unwrap_or(false) {
+        autopaste::remember_focus(app);
+    }
+
+    let _ = qb.center();
+    let _ = qb.show();
+    let _ = qb.set_focus();
+}
+
+pub fn hide(app: &AppHandle) {
+    if let Some(qb) = app.get_webview_window(LABEL) {
+        let _ = qb.hide();
+    }
+}
+
+pub fn toggle(app: &AppHandle) {
+    let visible = app
+        .get_webview_window(LABEL)
+        .and_then(|qb| qb.is_visible().ok())
+        .unwrap_or(false);
+
+    if visible {
+        hide(app);
+    } else {
+        show(app);
+    }
+}
+
+#[tauri::command]
+pub fn toggle_quickboard(app: AppHandle) {
+    toggle(&app);
+}
@@ -0,0 +1,374 @@
+// Every clipboard format an item was captured in, so copying it back restores the
+// original (formatted text, a set of files, an image) and not just its plain text.
+// Kept in item_representation next to the item, keyed by MIME type.
+// Reading goes through arboard where it can (HTML, files), RTF and all writes go
+// through native_clipboard, which puts every format back in one transaction
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use image::{ImageFormat, RgbaImage};
+use rusqlite::params;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::clipboard_watcher::compute_hash;
+use crate::image_sandbox::ImageSandbox;
+use crate::storage::{Item, Store};
+use crate::{autopaste, native_clipboard, quickboard};
+
+pub const TEXT: &str = "text/plain";
+pub const HTML: &str = "text/html";
+pub const PNG: &str = "image/png";
+pub const URI_LIST: &str = "text/uri-list";
+pub const RTF: &str = "text/rtf";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item_representation (
+    item_id INTEGER NOT NULL,
+    format VARCHAR NOT NULL,
+    data BLOB NOT NULL,
+    PRIMARY KEY (item_id, format)
+);
+";
+
+#[derive(Serialize)]
+pub struct Copied {
+    pub id: i64,
+    /// What ended up on the clipboard
+    pub formats: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct Representation {
+    pub format: String,
+    pub data: Vec<u8>,
+}
+
+impl Representation {
+    pub fn new(format: &str, data: impl Into<Vec<u8>>) -> Self {
+        Representation {
+            format: format.to_string(),
+            data: data.into(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).into_owned()
+    }
+}
+
+/// arboard handle for the formats the clipboard plugin doesn't read (HTML, file lists).
+/// Kept alive for the whole run, on X11 the clipboard contents go away with it
+#[derive(Default)]
+pub struct RichClipboard {
+    inner: Mutex<Option<arboard::Clipboard>>,
+}
+
+impl RichClipboard {
+    fn with<T>(&self, f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.is_none() {
+            *inner = Some(arboard::Clipboard::new().map_err(|e| format!("Clipboard unavailable: {}", e))?);
+        }
+        let clipboard = inner.as_mut().expect("clipboard was just created");
+        f(clipboard).map_err(|e| e.to_string())
+    }
+
+    pub fn read_html(&self) -> Option<String> {
+        self.with(|c| c.get().html()).ok().filter(|html| !html.trim().is_empty())
+    }
+
+    pub fn read_files(&self) -> Vec<PathBuf> {
+        self.with(|c| c.get().file_list()).unwrap_or_default()
+    }
+
+    /// Not through arboard, it doesn't know RTF
+    pub fn read_rtf(&self) -> Option<Vec<u8>> {
+        native_clipboard::read(RTF)
+    }
+}
+
+/// What `copy` last put on the clipboard, hashed the way the clipboard watcher
+/// hashes what it reads, so ClipMind's own copies aren't captured as new items
+#[derive(Default)]
+pub struct LastCopy {
+    hash: Mutex<Option<String>>,
+}
+
+impl LastCopy {
+    fn set(&self, hash: Option<String>) {
+        *self.hash.lock().unwrap_or_else(|e| e.into_inner()) = hash;
+    }
+
+    pub fn is(&self, content_hash: &str) -> bool {
+        self.hash.lock().unwrap_or_else(|e| e.into_inner()).as_deref() == Some(content_hash)
+    }
+}
+
+/// The hash the watcher will see for `reps`: files before text before the image,
+/// the same order poll_once reads them in
+fn watcher_hash(reps: &[Representation]) -> Option<String> {
+    let find = |format: &str| reps.iter().find(|r| r.format == format);
+    if let Some(files) = find(URI_LIST) {
+        return Some(compute_hash(uri_list(&parse_uri_list(&files.text())).as_bytes()));
+    }
+    if let Some(text) = find(TEXT) {
+        return Some(compute_hash(text.text().trim().as_bytes()));
+    }
+    let png = find(PNG)?;
+    let img = image::load_from_memory_with_format(&png.data, ImageFormat::Png).ok()?.to_rgba8();
+    Some(compute_hash(img.as_raw()))
+}
+
+/// file:// URIs, one per line as in RFC 2483
+pub fn uri_list(paths: &[PathBuf]) -> String {
+    paths.iter().map(|p| file_uri(p)).collect::<Vec<_>>().join("\r\n")
+}
+
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // Windows paths (C:/...) need the extra slash
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub fn parse_uri_list(list: &str) -> Vec<PathBuf> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.strip_prefix("file://"))
+        .map(|path| {
+            let path = percent_decode(path);
+            // file:///C:/x -> C:/x
+            match path.as_bytes() {
+                [b'/', _, b':', ..] => PathBuf::from(&path[1..]),
+                _ => PathBuf::from(path),
+            }
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = s.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// PNG bytes for raw RGBA clipboard pixels
+pub fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let img = RgbaImage::from_raw(width, height, rgba.to_vec()).ok_or("Image data doesn't match its size")?;
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(png)
+}
+
+pub fn save(store: &Store, item_id: i64, representations: &[Representation]) -> rusqlite::Result<()> {
+    store.with_conn(|conn| {
+        let mut stmt = conn.prepare_cached(
+            "INSERT OR REPLACE INTO item_representation (item_id, format, data) VALUES (?1, ?2, ?3)",
+        )?;
+        for rep in representations {
+            stmt.execute(params![item_id, rep.format, rep.data])?;
+        }
+        Ok(())
+    })
+}
+
+pub fn load(store: &Store, item_id: i64) -> rusqlite::Result<Vec<Representation>> {
+    store.with_conn(|conn| {
+        let mut stmt = conn.prepare_cached("SELECT format, data FROM item_representation WHERE item_id = ?1")?;
+        let rows = stmt.query_map([item_id], |row| {
+            Ok(Representation {
+                format: row.get(0)?,
+                data: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+/// Formats `item` can be copied as. Plain text is the item's own text rather than a
+/// stored copy, so a redacted or expired secret can't linger in this table
+fn available(item: &Item, stored: &[Representation]) -> Vec<Representation> {
+    let mut reps: Vec<Representation> = stored.iter().filter(|r| r.format != TEXT).cloned().collect();
+    reps.push(Representation::new(TEXT, item.text.as_bytes()));
+    reps
+}
+
+/// What the item was copied as: an image on its own, anything else with its plain text
+fn original(app: &AppHandle, reps: Vec<Representation>, screenshot: Option<&str>) -> Result<Vec<Representation>, String> {
+    if reps.iter().any(|r| r.format == PNG) {
+        return Ok(reps.into_iter().filter(|r| r.format == PNG).collect());
+    }
+    if let Some(path) = screenshot {
+        return Ok(vec![screenshot_png(app, path)?]);
+    }
+    Ok(reps)
+}
+
+/// Screenshots from before representations existed, read from their file
+fn screenshot_png(app: &AppHandle, path: &str) -> Result<Representation, String> {
+    let img = app
+        .state::<ImageSandbox>()
+        .open(path)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+    let (width, height) = img.dimensions();
+    Ok(Representation::new(PNG, encode_png(img.as_raw(), width, height)?))
+}
+
+/// Restore item `id` to the clipboard, as just `format` or in every format it was
+/// captured in, all set together. Returns the formats that were written
+pub fn copy(app: &AppHandle, id: i64, format: Option<&str>) -> Result<Vec<String>, String> {
+    let store = app.state::<Store>();
+    let item = store
+        .get(id)
+        .map_err(|e| format!("Failed to load item: {}", e))?
+        .ok_or_else(|| format!("Item {} not found", id))?;
+    let stored = load(&store, id).map_err(|e| format!("Failed to load item formats: {}", e))?;
+    let reps = available(&item, &stored);
+
+    let screenshot = item.blob_uri.as_deref().filter(|_| item.source == "screenshot");
+    let find = |format: &str| reps.iter().find(|r| r.format == format).cloned();
+
+    let chosen = match format {
+        Some(PNG) if find(PNG).is_none() => {
+            let path = screenshot.ok_or_else(|| format!("Item {} has no {} format", id, PNG))?;
+            vec![screenshot_png(app, path)?]
+        }
+        Some(format) => vec![find(format).ok_or_else(|| format!("Item {} has no {} format", id, format))?],
+        None => original(app, reps, screenshot)?,
+    };
+
+    // Before writing, the watcher may poll right after
+    let last_copy = app.state::<LastCopy>();
+    last_copy.set(watcher_hash(&chosen));
+    if let Err(e) = native_clipboard::write(&chosen) {
+        last_copy.set(None);
+        return Err(format!("Failed to copy: {}", e));
+    }
+    Ok(chosen.into_iter().map(|rep| rep.format).collect())
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    app.manage(RichClipboard::default());
+    app.manage(LastCopy::default());
+    app.state::<Store>()
+        .with_conn(|conn| {
+            conn.execute_batch(SCHEMA)?;
+            // Items deleted by the Python side or an older build
+            conn.execute(
+                "DELETE FROM item_representation WHERE item_id NOT IN (SELECT id FROM item)",
+                [],
+            )?;
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to create representation table: {}", e))
+}
+
+/// Formats item `id` can be copied as
+#[tauri::command]
+pub async fn item_formats(store: State<'_, Store>, id: i64) -> Result<Vec<String>, String> {
+    let item = store
+        .get(id)
+        .map_err(|e| format!("Failed to load item: {}", e))?
+        .ok_or_else(|| format!("Item {} not found", id))?;
+    let stored = load(&store, id).map_err(|e| format!("Failed to load item formats: {}", e))?;
+
+    let mut formats: Vec<String> = available(&item, &stored).into_iter().map(|rep| rep.format).collect();
+    if item.source == "screenshot" && item.blob_uri.is_some() && !formats.iter().any(|f| f == PNG) {
+        formats.push(PNG.to_string());
+    }
+    Ok(formats)
+}
+
+/// Copy item `id` back to the clipboard, in its original formats unless `format` picks one.
+/// With `hide` the quickboard is hidden afterwards, whether the copy worked or not,
+/// and a successful copy is pasted into the previous app when auto-paste is on
+#[tauri::command]
+pub async fn copy_item(app: AppHandle, id: i64, format: Option<String>, hide: Option<bool>) -> Result<Copied, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = if hide.unwrap_or(false) {
+            autopaste::paste_after(&app, || {
+                let result = copy(&app, id, format.as_deref());
+                quickboard::hide(&app);
+                result
+            })
+        } else {
+            copy(&app, id, format.as_deref())
+        };
+        result.map(|formats| Copied { id, formats })
+    })
+    .await
+    .map_err(|e| format!("Copy failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_list_escapes_and_joins() {
+        let paths = [PathBuf::from("/home/me/My Notes.txt"), PathBuf::from("C:/Users/Zoë/a&b.png")];
+        assert_eq!(
+            uri_list(&paths),
+            "file:///home/me/My%20Notes.txt\r\nfile:///C:/Users/Zo%C3%AB/a%26b.png"
+        );
+    }
+
+    #[test]
+    fn uri_list_round_trips() {
+        let paths = vec![
+            PathBuf::from("/tmp/with space/file name.txt"),
+            PathBuf::from("/home/jürgen/Документы/日本語.pdf"),
+            PathBuf::from("C:/Program Files/ClipMind/100% done.png"),
+            PathBuf::from("/plain/path-with_safe.chars~"),
+        ];
+        assert_eq!(parse_uri_list(&uri_list(&paths)), paths);
+    }
+
+    #[test]
+    fn windows_backslashes_become_slashes() {
+        let list = uri_list(&[PathBuf::from(r"C:\Users\me\shot 1.png")]);
+        assert_eq!(list, "file:///C:/Users/me/shot%201.png");
+        assert_eq!(parse_uri_list(&list), [PathBuf::from("C:/Users/me/shot 1.png")]);
+    }
+
+    #[test]
+    fn parse_uri_list_skips_comments_and_other_schemes() {
+        let list = "# copied from a file manager\n  file:///a/b.txt  \n\nhttps://example.com/x\nfile:///c%2Fd\n";
+        assert_eq!(parse_uri_list(list), [PathBuf::from("/a/b.txt"), PathBuf::from("/c/d")]);
+    }
+
+    #[test]
+    fn percent_decode_leaves_bad_escapes_alone() {
+        assert_eq!(percent_decode("a%20b"), "a b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%C3%A9t%C3%A9"), "été");
+    }
+}
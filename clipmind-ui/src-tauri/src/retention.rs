@@ -0,0 +1,529 @@
+// Retention policies, so history doesn't grow forever.
+// Rules are per source ([retention.rules.clipboard], [retention.rules.screenshot]) with
+// a max age, a max item count and a max size, sources without a rule are kept forever.
+// Pinned items are never removed, tagged and collected ones by default neither, and
+// exempt items don't count toward the limits. A sweep runs every sweep_interval_mins.
+//
+// `purge` is the one place items get deleted with everything hanging off them: the
+// side tables, owned blobs, cached thumbnails, and the FAISS vectors through a
+// tombstone the Python side drops on its next load (see DualVectorStore.purge_deleted)
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::storage::{self, now_ts, Store};
+use crate::{settings, thumbnail, vault};
+
+// Matches the DDL SQLModel emits for IndexTombstone in app/db/models.py
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS index_tombstone (
+    item_id INTEGER NOT NULL,
+    deleted_ts INTEGER NOT NULL,
+    PRIMARY KEY (item_id)
+);
+";
+
+// Tables keyed by an item id, cleared along with the item
+const CASCADE: &[(&str, &str)] = &[
+    ("item_representation", "item_id"),
+    ("item_fingerprint", "item_id"),
+    ("ocr_job", "item_id"),
+    ("item_expiry", "item_id"),
+    ("item_pin", "item_id"),
+    ("item_tag", "item_id"),
+    ("collection_item", "item_id"),
+    ("capture_reject", "duplicate_of"),
+];
+
+// Give startup (backend, watchers, ledger reconcile) some room before the first sweep
+const FIRST_SWEEP_SECS: u64 = 60;
+
+// The Python side loads the index at least this often, older tombstones are done
+const TOMBSTONE_DAYS: i64 = 30;
+
+const DAY_SECS: i64 = 24 * 3600;
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionRule {
+    pub max_age_days: Option<u32>,
+    pub max_items: Option<u32>,
+    /// Text, clipboard representations and blobs ClipMind owns. Screenshot files
+    /// belong to the user and are never deleted, so they don't count
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    pub sweep_interval_mins: u64,
+    pub keep_tagged: bool,
+    pub keep_in_collections: bool,
+    /// Keyed by source
+    pub rules: BTreeMap<String, RetentionRule>,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        RetentionSettings {
+            sweep_interval_mins: 60,
+            keep_tagged: true,
+            keep_in_collections: true,
+            rules: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Age,
+    Count,
+    Bytes,
+}
+
+#[derive(Serialize)]
+pub struct Removal {
+    pub id: i64,
+    pub source: String,
+    pub created_ts: i64,
+    pub bytes: u64,
+    pub reason: Reason,
+}
+
+#[derive(Serialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub scanned: usize,
+    /// Pinned, or tagged / in a collection when those are kept
+    pub exempt: usize,
+    pub removed: Vec<Removal>,
+    pub freed_bytes: u64,
+    /// Blobs and thumbnails, 0 on a dry run
+    pub files_deleted: usize,
+}
+
+#[derive(Default)]
+pub struct Purged {
+    pub items: usize,
+    pub files: usize,
+}
+
+struct Row {
+    id: i64,
+    source: String,
+    created_ts: i64,
+    bytes: u64,
+    exempt: bool,
+}
+
+/// Path of a blob ClipMind wrote itself, None for screenshots and anything else it only points at
+fn owned_blob(blob_dir: Option<&Path>, blob_uri: Option<&str>) -> Option<PathBuf> {
+    let path = fs::canonicalize(blob_uri?).ok()?;
+    path.starts_with(blob_dir?).then_some(path)
+}
+
+/// Pinned items are always kept, tagged and collected ones when the settings say so
+fn is_exempt(settings: &RetentionSettings, pinned: bool, tagged: bool, collected: bool) -> bool {
+    pinned || (settings.keep_tagged && tagged) || (settings.keep_in_collections && collected)
+}
+
+/// Every item newest first, with its size and whether the settings exempt it
+fn rows(app: &AppHandle, settings: &RetentionSettings) -> Result<Vec<Row>, String> {
+    let blob_dir = storage::blob_dir(app).ok().and_then(|dir| fs::canonicalize(dir).ok());
+    let items = app
+        .state::<Store>()
+        .with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, source, created_ts, blob_uri,
+                        LENGTH(CAST(text AS BLOB))
+                            + COALESCE((SELECT SUM(LENGTH(data)) FROM item_representation WHERE item_id = item.id), 0),
+                        id IN (SELECT item_id FROM item_pin),
+                        id IN (SELECT item_id FROM item_tag),
+                        id IN (SELECT item_id FROM collection_item)
+                 FROM item ORDER BY created_ts DESC, id DESC",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                let blob_uri: Option<String> = row.get(3)?;
+                let (pinned, tagged, collected): (bool, bool, bool) = (row.get(5)?, row.get(6)?, row.get(7)?);
+                Ok((
+                    Row {
+                        id: row.get(0)?,
+                        source: row.get(1)?,
+                        created_ts: row.get(2)?,
+                        bytes: row.get::<_, i64>(4)?.max(0) as u64,
+                        exempt: is_exempt(settings, pinned, tagged, collected),
+                    },
+                    blob_uri,
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| format!("Failed to scan items: {}", e))?;
+
+    Ok(items
+        .into_iter()
+        .map(|(mut row, blob_uri)| {
+            if let Some(path) = owned_blob(blob_dir.as_deref(), blob_uri.as_deref()) {
+                row.bytes += fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            }
+            row
+        })
+        .collect())
+}
+
+/// What the rules would remove. `rows` must be newest first, so the limits keep the latest items
+fn plan(settings: &RetentionSettings, rows: &[Row], now: i64) -> Vec<Removal> {
+    // Items and bytes kept so far, per source
+    let mut kept: HashMap<&str, (u32, u64)> = HashMap::new();
+    let mut removed = Vec::new();
+
+    for row in rows.iter().filter(|row| !row.exempt) {
+        let Some(rule) = settings.rules.get(&row.source) else {
+            continue;
+        };
+        let (count, bytes) = kept.entry(row.source.as_str()).or_default();
+
+        let reason = if rule.max_age_days.is_some_and(|days| row.created_ts < now - days as i64 * DAY_SECS) {
+            Some(Reason::Age)
+        } else if rule.max_items.is_some_and(|max| *count >= max) {
+            Some(Reason::Count)
+        } else if rule.max_bytes.is_some_and(|max| *bytes + row.bytes > max) {
+            Some(Reason::Bytes)
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => removed.push(Removal {
+                id: row.id,
+                source: row.source.clone(),
+                created_ts: row.created_ts,
+                bytes: row.bytes,
+                reason,
+            }),
+            None => {
+                *count += 1;
+                *bytes += row.bytes;
+            }
+        }
+    }
+
+    removed
+}
+
+fn delete_rows(conn: &Connection, ids: &[i64]) -> rusqlite::Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut deleted = 0;
+    for &id in ids {
+        let gone = tx.execute("DELETE FROM item WHERE id = ?1", [id])?;
+        if gone == 0 {
+            continue;
+        }
+        deleted += gone;
+        for (table, column) in CASCADE {
+            tx.execute(&format!("DELETE FROM {} WHERE {} = ?1", table, column), [id])?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO index_tombstone (item_id, deleted_ts) VALUES (?1, ?2)",
+            params![id, now_ts()],
+        )?;
+    }
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Delete `ids` and everything that belongs to them. Screenshot files stay where
+/// they are, and so do their ledger rows, so the watcher doesn't ingest them again
+pub fn purge(app: &AppHandle, ids: &[i64]) -> Result<Purged, String> {
+    if ids.is_empty() {
+        return Ok(Purged::default());
+    }
+
+    let store = app.state::<Store>();
+    let mut blobs = Vec::new();
+    for &id in ids {
+        if let Some(blob_uri) = store
+            .get(id)
+            .map_err(|e| format!("Failed to load item {}: {}", id, e))?
+            .and_then(|item| item.blob_uri)
+        {
+            blobs.push(blob_uri);
+        }
+    }
+
+    let items = store
+        .with_conn(|conn| delete_rows(conn, ids))
+        .map_err(|e| format!("Failed to delete items: {}", e))?;
+
+    let blob_dir = storage::blob_dir(app).ok().and_then(|dir| fs::canonicalize(dir).ok());
+    let cache_dir = thumbnail::cache_dir(app).ok();
+    let mut files = 0;
+    for blob_uri in blobs {
+        // Thumbnails are keyed by the canonical path, same as the sandbox hands out
+        let Ok(path) = fs::canonicalize(&blob_uri) else {
+            continue;
+        };
+        if let Some(cache_dir) = &cache_dir {
+            files += thumbnail::remove_cached(cache_dir, &path);
+        }
+        if owned_blob(blob_dir.as_deref(), Some(&blob_uri)).is_some() && fs::remove_file(&path).is_ok() {
+            files += 1;
+        }
+    }
+
+    Ok(Purged { items, files })
+}
+
+/// Apply the rules once. With `dry_run` nothing is deleted, the report shows what would be
+pub fn run(app: &AppHandle, settings: &RetentionSettings, dry_run: bool) -> Result<RetentionReport, String> {
+    let rows = rows(app, settings)?;
+    let removed = plan(settings, &rows, now_ts());
+
+    let purged = if dry_run {
+        Purged::default()
+    } else {
+        let ids: Vec<i64> = removed.iter().map(|r| r.id).collect();
+        purge(app, &ids)?
+    };
+
+    Ok(RetentionReport {
+        dry_run,
+        scanned: rows.len(),
+        exempt: rows.iter().filter(|row| row.exempt).count(),
+        freed_bytes: removed.iter().map(|r| r.bytes).sum(),
+        removed,
+        files_deleted: purged.files,
+    })
+}
+
+fn prune_tombstones(store: &Store) -> rusqlite::Result<usize> {
+    store.with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM index_tombstone WHERE deleted_ts < ?1",
+            [now_ts() - TOMBSTONE_DAYS * DAY_SECS],
+        )
+    })
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    app.state::<Store>()
+        .with_conn(|conn| conn.execute_batch(SCHEMA))
+        .map_err(|e| format!("Failed to create tombstone table: {}", e))?;
+
+    let app = app.clone();
+    thread::Builder::new()
+        .name("retention-sweep".into())
+        .spawn(move || {
+            thread::sleep(Duration::from_secs(FIRST_SWEEP_SECS));
+            loop {
+                // Read every time round so edits to [retention] apply without a restart
+                let settings = settings::get(&app).retention;
+                // Nothing to sweep while the history is locked, it waits for the next round
+                if !vault::is_locked(&app) {
+                    if !settings.rules.is_empty() {
+                        match run(&app, &settings, false) {
+                            Ok(report) if report.removed.is_empty() => {}
+                            Ok(report) => eprintln!(
+                                "[RETENTION] Removed {} items ({} bytes, {} files)",
+                                report.removed.len(),
+                                report.freed_bytes,
+                                report.files_deleted
+                            ),
+                            Err(e) => eprintln!("[RETENTION] Sweep failed: {}", e),
+                        }
+                    }
+                    if let Err(e) = prune_tombstones(&app.state::<Store>()) {
+                        eprintln!("[RETENTION] Failed to prune tombstones: {}", e);
+                    }
+                }
+                thread::sleep(Duration::from_secs(settings.sweep_interval_mins * 60));
+            }
+        })
+        .map_err(|e| format!("Failed to start retention sweep: {}", e))?;
+
+    Ok(())
+}
+
+/// Apply the retention rules now, same as the scheduled sweep.
+/// With `dry_run` nothing is deleted, the report shows what would be
+#[tauri::command]
+pub async fn apply_retention(app: AppHandle, dry_run: bool) -> Result<RetentionReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = settings::get(&app).retention;
+        run(&app, &settings, dry_run)
+    })
+    .await
+    .map_err(|e| format!("Retention failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn row(id: i64, source: &str, age_days: i64, bytes: u64) -> Row {
+        Row {
+            id,
+            source: source.to_string(),
+            created_ts: NOW - age_days * DAY_SECS,
+            bytes,
+            exempt: false,
+        }
+    }
+
+    fn settings(rules: &[(&str, RetentionRule)]) -> RetentionSettings {
+        RetentionSettings {
+            rules: rules.iter().map(|(source, rule)| (source.to_string(), rule.clone())).collect(),
+            ..RetentionSettings::default()
+        }
+    }
+
+    fn removed(settings: &RetentionSettings, rows: &[Row]) -> Vec<(i64, &'static str)> {
+        plan(settings, rows, NOW)
+            .into_iter()
+            .map(|r| {
+                let reason = match r.reason {
+                    Reason::Age => "age",
+                    Reason::Count => "count",
+                    Reason::Bytes => "bytes",
+                };
+                (r.id, reason)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_rules_keeps_everything() {
+        let rows = [row(2, "clipboard", 400, 10), row(1, "screenshot", 900, 10)];
+        assert!(plan(&RetentionSettings::default(), &rows, NOW).is_empty());
+    }
+
+    #[test]
+    fn max_age_per_source() {
+        let settings = settings(&[(
+            "clipboard",
+            RetentionRule {
+                max_age_days: Some(30),
+                ..RetentionRule::default()
+            },
+        )]);
+        let rows = [
+            row(4, "clipboard", 1, 10),
+            row(3, "clipboard", 30, 10),
+            row(2, "screenshot", 60, 10),
+            row(1, "clipboard", 31, 10),
+        ];
+        // Exactly 30 days old is still in, screenshots have no rule
+        assert_eq!(removed(&settings, &rows), [(1, "age")]);
+    }
+
+    #[test]
+    fn max_items_keeps_the_newest() {
+        let settings = settings(&[
+            (
+                "clipboard",
+                RetentionRule {
+                    max_items: Some(2),
+                    ..RetentionRule::default()
+                },
+            ),
+            (
+                "screenshot",
+                RetentionRule {
+                    max_items: Some(1),
+                    ..RetentionRule::default()
+                },
+            ),
+        ]);
+        let rows = [
+            row(5, "clipboard", 0, 10),
+            row(4, "screenshot", 1, 10),
+            row(3, "clipboard", 2, 10),
+            row(2, "screenshot", 3, 10),
+            row(1, "clipboard", 4, 10),
+        ];
+        assert_eq!(removed(&settings, &rows), [(2, "count"), (1, "count")]);
+    }
+
+    #[test]
+    fn max_bytes_skips_what_doesnt_fit() {
+        let settings = settings(&[(
+            "clipboard",
+            RetentionRule {
+                max_bytes: Some(100),
+                ..RetentionRule::default()
+            },
+        )]);
+        let rows = [
+            row(4, "clipboard", 0, 60),
+            row(3, "clipboard", 1, 50),
+            row(2, "clipboard", 2, 40),
+            row(1, "clipboard", 3, 10),
+        ];
+        // A removed item doesn't use up any of the budget, smaller older ones can still fit
+        assert_eq!(removed(&settings, &rows), [(3, "bytes"), (1, "bytes")]);
+    }
+
+    #[test]
+    fn age_wins_over_count_and_bytes() {
+        let settings = settings(&[(
+            "clipboard",
+            RetentionRule {
+                max_age_days: Some(7),
+                max_items: Some(1),
+                max_bytes: Some(5),
+            },
+        )]);
+        let rows = [
+            row(3, "clipboard", 0, 1),
+            row(2, "clipboard", 1, 1),
+            row(1, "clipboard", 8, 100),
+        ];
+        assert_eq!(removed(&settings, &rows), [(2, "count"), (1, "age")]);
+    }
+
+    #[test]
+    fn exempt_items_are_kept_and_not_counted() {
+        let settings = settings(&[(
+            "clipboard",
+            RetentionRule {
+                max_age_days: Some(7),
+                max_items: Some(1),
+                ..RetentionRule::default()
+            },
+        )]);
+        let mut rows = [
+            row(4, "clipboard", 0, 10),
+            row(3, "clipboard", 1, 10),
+            row(2, "clipboard", 2, 10),
+            row(1, "clipboard", 100, 10),
+        ];
+        rows[0].exempt = true;
+        rows[3].exempt = true;
+        // The exempt newest item doesn't take the only slot
+        assert_eq!(removed(&settings, &rows), [(2, "count")]);
+    }
+
+    #[test]
+    fn exemptions_follow_the_settings() {
+        let mut settings = RetentionSettings::default();
+        assert!(is_exempt(&settings, true, false, false));
+        assert!(is_exempt(&settings, false, true, false));
+        assert!(is_exempt(&settings, false, false, true));
+        assert!(!is_exempt(&settings, false, false, false));
+
+        settings.keep_tagged = false;
+        settings.keep_in_collections = false;
+        assert!(is_exempt(&settings, true, true, true));
+        assert!(!is_exempt(&settings, false, true, true));
+    }
+}
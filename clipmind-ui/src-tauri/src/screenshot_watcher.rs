@@ -0,0 +1,293 @@
+// Native screenshot folder watcher, replaces the polling loop in app/ingest/screenshot_watcher.py
+// Listens for filesystem notifications on the configured folders, waits until a
+// new file stops changing, then hands it to fingerprint::ingest. OCR is still done
+// in Python, app/ingest/ocr_worker.py works through the jobs ingest queues
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use image::ImageReader;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::fingerprint::{self, IngestOutcome};
+use crate::storage::Store;
+use crate::{ledger, settings};
+
+pub const DETECTED_EVENT: &str = "screenshot://detected";
+
+/// Same list as IMAGE_EXTENSIONS in screenshot_watcher.py
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "tiff", "webp"];
+
+// How often pending files are checked for being done
+const TICK_MS: u64 = 250;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WatchRoot {
+    pub path: PathBuf,
+    #[serde(default = "default_recursive")]
+    pub recursive: bool,
+}
+
+fn default_recursive() -> bool {
+    true
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatcherConfig {
+    pub enabled: bool,
+    /// Empty means the folder screenshot_watcher.py would pick
+    pub roots: Vec<WatchRoot>,
+    /// Globs matched against the full path, empty includes everything
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// How long a file has to stay unchanged before it's picked up
+    pub debounce_ms: u64,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        WatcherConfig {
+            enabled: true,
+            roots: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            debounce_ms: 1500,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct Detected {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: u64,
+}
+
+/// A file we got events for but that may still be being written
+struct Pending {
+    last_change: Instant,
+    size: Option<u64>,
+}
+
+struct Filter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Filter {
+    fn new(config: &WatcherConfig) -> Result<Self, String> {
+        let include = if config.include.is_empty() {
+            None
+        } else {
+            Some(glob_set(&config.include)?)
+        };
+        Ok(Filter {
+            include,
+            exclude: glob_set(&config.exclude)?,
+        })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        if !is_image_path(path) || self.exclude.is_match(path) {
+            return false;
+        }
+        self.include.as_ref().is_none_or(|set| set.is_match(path))
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid glob {:?}: {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| format!("Failed to build globs: {}", e))
+}
+
+pub fn is_image_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Folders to watch, defaults to OneDrive/Pictures or Pictures like the Python watcher did
+pub fn roots(app: &AppHandle) -> Vec<WatchRoot> {
+    let config = settings::get(app).screenshots;
+    if !config.roots.is_empty() {
+        return config.roots;
+    }
+
+    let Ok(home) = app.path().home_dir() else {
+        return Vec::new();
+    };
+    let onedrive = home.join("OneDrive").join("Pictures");
+    let path = if onedrive.exists() { onedrive } else { home.join("Pictures") };
+    vec![WatchRoot { path, recursive: true }]
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    let config = settings::get(app).screenshots;
+    if !config.enabled {
+        eprintln!("[SCREENSHOTS] Watcher disabled in settings");
+        return Ok(());
+    }
+    let filter = Filter::new(&config)?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    let mut watching = 0;
+    for root in roots(app) {
+        let mode = if root.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        // A missing folder shouldn't take the rest of the app down
+        match watcher.watch(&root.path, mode) {
+            Ok(()) => {
+                eprintln!("[SCREENSHOTS] Watching {}", root.path.display());
+                watching += 1;
+            }
+            Err(e) => eprintln!("[SCREENSHOTS] Can't watch {}: {}", root.path.display(), e),
+        }
+    }
+    if watching == 0 {
+        eprintln!("[SCREENSHOTS] No folders to watch");
+        return Ok(());
+    }
+
+    let app = app.clone();
+    let debounce = Duration::from_millis(config.debounce_ms);
+    thread::Builder::new()
+        .name("screenshot-watcher".into())
+        .spawn(move || {
+            // Owned by the thread so notifications keep coming
+            let _watcher = watcher;
+            let mut pending: HashMap<PathBuf, Pending> = HashMap::new();
+
+            loop {
+                match rx.recv_timeout(Duration::from_millis(TICK_MS)) {
+                    Ok(Ok(event)) => track(&mut pending, &filter, event),
+                    Ok(Err(e)) => eprintln!("[SCREENSHOTS] Watch error: {}", e),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                for path in settled(&mut pending, debounce) {
+                    process(&app, &path);
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start screenshot watcher: {}", e))?;
+
+    Ok(())
+}
+
+fn track(pending: &mut HashMap<PathBuf, Pending>, filter: &Filter, event: Event) {
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(_) => {
+            for path in event.paths.into_iter().filter(|p| filter.matches(p)) {
+                let size = fs::metadata(&path).ok().map(|meta| meta.len());
+                pending.insert(
+                    path,
+                    Pending {
+                        last_change: Instant::now(),
+                        size,
+                    },
+                );
+            }
+        }
+        EventKind::Remove(_) => {
+            for path in &event.paths {
+                pending.remove(path);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Files that have been quiet for `debounce` and are still the size they were at the last event
+fn settled(pending: &mut HashMap<PathBuf, Pending>, debounce: Duration) -> Vec<PathBuf> {
+    let mut ready = Vec::new();
+
+    pending.retain(|path, entry| {
+        if entry.last_change.elapsed() < debounce {
+            return true;
+        }
+        let size = match fs::metadata(path) {
+            Ok(meta) if meta.is_file() => meta.len(),
+            // Renamed away or deleted before we got to it
+            _ => return false,
+        };
+
+        if entry.size == Some(size) {
+            // Nothing to do for a file that stayed empty
+            if size > 0 {
+                ready.push(path.clone());
+            }
+            return false;
+        }
+        // Still growing, check again after another debounce
+        entry.size = Some(size);
+        entry.last_change = Instant::now();
+        true
+    });
+
+    ready
+}
+
+fn process(app: &AppHandle, path: &Path) {
+    // Ledger rows are keyed by the canonical path, same as blob_uri
+    let Ok(canonical) = fs::canonicalize(path) else {
+        return;
+    };
+    let path = canonical.as_path();
+    let store = app.state::<Store>();
+    if ledger::is_done(&store, path) {
+        return;
+    }
+
+    // Only reads the header, a file that doesn't parse isn't an image we can use
+    let dimensions = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| e.to_string())
+        .and_then(|reader| reader.into_dimensions().map_err(|e| e.to_string()));
+    let (width, height) = match dimensions {
+        Ok(dims) => dims,
+        Err(e) => {
+            eprintln!("[SCREENSHOTS] Skipping {}: {}", path.display(), e);
+            ledger::record(&store, path, None, &Err(format!("Not a readable image: {}", e)));
+            return;
+        }
+    };
+
+    let path_str = path.to_string_lossy().into_owned();
+    eprintln!("[SCREENSHOTS] Detected {} ({}x{})", path_str, width, height);
+    let _ = app.emit(
+        DETECTED_EVENT,
+        Detected {
+            path: path_str.clone(),
+            width,
+            height,
+            bytes: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        },
+    );
+
+    match fingerprint::ingest(app, &path_str) {
+        Ok(IngestOutcome::Inserted { item_id }) => eprintln!("[SCREENSHOTS] Saved item {}", item_id),
+        Ok(IngestOutcome::Duplicate { item_id }) => eprintln!("[SCREENSHOTS] Duplicate of item {}", item_id),
+        // fingerprint::ingest already logged near duplicates
+        Ok(_) => {}
+        Err(e) => eprintln!("[SCREENSHOTS] Failed to ingest {}: {}", path_str, e),
+    }
+}
@@ -0,0 +1,579 @@
+// Offline keyword search over the item table
+// In-memory inverted index (exact, prefix and fuzzy term matching) scored with
+// BM25 and boosted by recency. Keeps itself in sync with the database lazily:
+// new rows by id, rows changed in place (OCR text filled in, a moved file) through
+// item_revision, which triggers keep up to date for every writer including Python
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::fingerprint;
+use crate::storage::{now_ts, Item, Store, ITEM_COLUMNS};
+use crate::tags;
+
+// Same default as top_k_results in app/core/config.py
+const DEFAULT_K: usize = 5;
+const MAX_K: usize = 100;
+
+// BM25 parameters
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+// How much a partial match is worth compared to an exact one
+const PREFIX_WEIGHT: f32 = 0.8;
+const FUZZY_WEIGHT: f32 = 0.6;
+
+// Brand new items score up to 50% higher, halving every week
+const RECENCY_WEIGHT: f32 = 0.5;
+const RECENCY_HALF_LIFE_SECS: f32 = 7.0 * 86400.0;
+
+// Rows are never removed, so MAX(revision) only goes up and a deleted item
+// can't hide a later change. It's at most one row per item that was ever updated
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item_revision (
+    item_id INTEGER NOT NULL PRIMARY KEY,
+    revision INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ix_item_revision_revision ON item_revision (revision);
+CREATE TRIGGER IF NOT EXISTS item_revision_update AFTER UPDATE ON item
+BEGIN
+    INSERT OR REPLACE INTO item_revision (item_id, revision)
+    VALUES (NEW.id, (SELECT COALESCE(MAX(revision), 0) + 1 FROM item_revision));
+END;
+";
+
+// Placeholders the screenshot watchers store when there is no OCR text (yet)
+const OCR_PLACEHOLDERS: &[&str] = &[
+    "[No text detected]",
+    "[No text detected - OCR unavailable]",
+    fingerprint::OCR_PENDING,
+];
+
+#[derive(Serialize)]
+pub struct TagReport {
+    pub matched: usize,
+    /// Tags newly added, items that already had them don't count
+    pub tagged: usize,
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub item: Item,
+    pub score: f32,
+    pub preview: String,
+}
+
+struct Doc {
+    item: Item,
+    len: u32,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    inner: Mutex<Inner>,
+}
+
+impl SearchIndex {
+    /// Forget every indexed item, the next search reads them back from the db
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap_or_else(|e| e.into_inner()) = Inner::default();
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    docs: HashMap<i64, Doc>,
+    // term -> (item id -> term frequency), ordered so prefix lookups are a range scan
+    terms: BTreeMap<String, HashMap<i64, u32>>,
+    total_len: u64,
+    max_id: i64,
+    revision: i64,
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn item_tokens(item: &Item) -> Vec<String> {
+    if OCR_PLACEHOLDERS.contains(&item.text.as_str()) {
+        Vec::new()
+    } else {
+        tokenize(&item.text)
+    }
+}
+
+/// Items updated after `revision`, that are still around
+fn changed_since(store: &Store, revision: i64) -> rusqlite::Result<Vec<Item>> {
+    store.with_conn(|conn| {
+        let sql = format!(
+            "SELECT {} FROM item JOIN item_revision ON item_revision.item_id = item.id
+             WHERE item_revision.revision > ?1",
+            ITEM_COLUMNS
+        );
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let rows = stmt.query_map([revision], Item::from_row)?;
+        rows.collect()
+    })
+}
+
+impl Inner {
+    fn add(&mut self, item: Item) {
+        let tokens = item_tokens(&item);
+
+        for token in &tokens {
+            *self.terms.entry(token.clone()).or_default().entry(item.id).or_insert(0) += 1;
+        }
+
+        self.total_len += tokens.len() as u64;
+        self.max_id = self.max_id.max(item.id);
+        self.docs.insert(
+            item.id,
+            Doc {
+                item,
+                len: tokens.len() as u32,
+            },
+        );
+    }
+
+    /// Take an item out again, no-op when it isn't indexed
+    fn remove(&mut self, id: i64) {
+        let Some(doc) = self.docs.remove(&id) else {
+            return;
+        };
+        for token in item_tokens(&doc.item) {
+            if let Some(postings) = self.terms.get_mut(&token) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.terms.remove(&token);
+                }
+            }
+        }
+        self.total_len -= doc.len as u64;
+    }
+
+    /// Pull in rows added or changed since the last search, rebuilding from
+    /// scratch when rows disappeared (deleted from here or from the Python side)
+    fn sync(&mut self, store: &Store) -> rusqlite::Result<()> {
+        let (max_id, count, revision): (i64, usize, i64) = store.with_conn(|conn| {
+            conn.query_row(
+                "SELECT COALESCE(MAX(id), 0), COUNT(*), (SELECT COALESCE(MAX(revision), 0) FROM item_revision) FROM item",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+        })?;
+
+        if max_id > self.max_id {
+            for item in store.items_after_id(self.max_id)? {
+                self.add(item);
+            }
+        }
+
+        if revision > self.revision {
+            for item in changed_since(store, self.revision)? {
+                self.remove(item.id);
+                self.add(item);
+            }
+            self.revision = revision;
+        }
+
+        // Something was deleted below our high-water mark, start over.
+        // Fresh rows already reflect every change, so the revision carries over
+        if count != self.docs.len() {
+            *self = Inner {
+                revision: self.revision,
+                ..Inner::default()
+            };
+            for item in store.items_after_id(0)? {
+                self.add(item);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Matching terms in the vocabulary for one query term, with a match weight
+    fn expand(&self, query_term: &str) -> Vec<(&String, f32)> {
+        let mut matches: HashMap<&String, f32> = HashMap::new();
+
+        // Exact and prefix matches
+        for (term, _) in self.terms.range(query_term.to_string()..) {
+            if !term.starts_with(query_term) {
+                break;
+            }
+            let weight = if term == query_term { 1.0 } else { PREFIX_WEIGHT };
+            matches.insert(term, weight);
+        }
+
+        // Typos, only for terms long enough that an edit doesn't change the word
+        let query_len = query_term.chars().count();
+        let max_edits = match query_len {
+            0..=3 => 0,
+            4..=7 => 1,
+            _ => 2,
+        };
+        if max_edits > 0 {
+            for term in self.terms.keys() {
+                if matches.contains_key(term) || term.chars().count().abs_diff(query_len) > max_edits {
+                    continue;
+                }
+                if levenshtein_within(query_term, term, max_edits) {
+                    matches.insert(term, FUZZY_WEIGHT);
+                }
+            }
+        }
+
+        matches.into_iter().collect()
+    }
+
+    fn keep(item: &Item, source: Option<&str>, after: Option<i64>, allowed: Option<&HashSet<i64>>) -> bool {
+        source.is_none_or(|s| item.source == s)
+            // Inclusive, same as Store::recent and the Python search
+            && after.is_none_or(|a| item.created_ts >= a)
+            && allowed.is_none_or(|ids| ids.contains(&item.id))
+    }
+
+    /// Most recent of the `allowed` items, for queries that are only filters
+    fn browse(&self, source: Option<&str>, after: Option<i64>, allowed: &HashSet<i64>, k: usize) -> Vec<SearchResult> {
+        let mut items: Vec<&Item> = allowed
+            .iter()
+            .filter_map(|id| self.docs.get(id))
+            .map(|doc| &doc.item)
+            .filter(|item| Self::keep(item, source, after, None))
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse((item.created_ts, item.id)));
+        items
+            .into_iter()
+            .take(k)
+            .map(|item| SearchResult {
+                item: item.clone(),
+                score: 0.0,
+                preview: preview(&item.text),
+            })
+            .collect()
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        source: Option<&str>,
+        after: Option<i64>,
+        allowed: Option<&HashSet<i64>>,
+        k: usize,
+    ) -> Vec<SearchResult> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() || self.docs.is_empty() {
+            return Vec::new();
+        }
+
+        let doc_count = self.docs.len() as f32;
+        let avg_len = (self.total_len as f32 / doc_count).max(1.0);
+
+        // Every query term has to match something in the item
+        let mut scores: Option<HashMap<i64, f32>> = None;
+        for query_term in &query_terms {
+            let mut term_scores: HashMap<i64, f32> = HashMap::new();
+
+            for (term, weight) in self.expand(query_term) {
+                let postings = &self.terms[term];
+                let df = postings.len() as f32;
+                let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+                for (&id, &tf) in postings {
+                    let len = self.docs[&id].len as f32;
+                    let tf = tf as f32;
+                    let bm25 = idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len));
+                    let best = term_scores.entry(id).or_insert(0.0);
+                    *best = best.max(weight * bm25);
+                }
+            }
+
+            scores = Some(match scores {
+                None => term_scores,
+                Some(prev) => prev
+                    .into_iter()
+                    .filter_map(|(id, s)| term_scores.get(&id).map(|t| (id, s + t)))
+                    .collect(),
+            });
+        }
+
+        let now = now_ts();
+        let mut results: Vec<SearchResult> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, relevance)| {
+                let item = &self.docs[&id].item;
+                if !Self::keep(item, source, after, allowed) {
+                    return None;
+                }
+
+                let age = (now - item.created_ts).max(0) as f32;
+                let recency = 0.5f32.powf(age / RECENCY_HALF_LIFE_SECS);
+                Some(SearchResult {
+                    item: item.clone(),
+                    score: relevance * (1.0 + RECENCY_WEIGHT * recency),
+                    preview: preview(&item.text),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(k);
+        results
+    }
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    app.state::<Store>()
+        .with_conn(|conn| conn.execute_batch(SCHEMA))
+        .map_err(|e| format!("Failed to create search tables: {}", e))
+}
+
+/// First 80 characters, same as the preview in /search
+pub fn preview(text: &str) -> String {
+    match text.char_indices().nth(80) {
+        Some((cut, _)) => format!("{}...", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// Edit distance between `a` and `b` is at most `max`
+fn levenshtein_within(a: &str, b: &str, max: usize) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        let mut row_min = cur[0];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+            row_min = row_min.min(cur[j + 1]);
+        }
+        // The distance can only grow from here
+        if row_min > max {
+            return false;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()] <= max
+}
+
+/// Source filter for a search mode, same modes as /search
+fn mode_source(mode: &str) -> Result<Option<&'static str>, String> {
+    match mode {
+        "all" | "text" => Ok(None),
+        "images" => Ok(Some("screenshot")),
+        "clipboard" => Ok(Some("clipboard")),
+        other => Err(format!("Unknown search mode: {}", other)),
+    }
+}
+
+/// Keyword search narrowed by any `tag:` / `collection:` filters in the query.
+/// A query of only filters lists the matching items, newest first
+fn run(
+    store: &Store,
+    index: &SearchIndex,
+    query: &str,
+    mode: Option<&str>,
+    after: Option<i64>,
+    k: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let source = mode_source(mode.unwrap_or("all"))?;
+    let (text, filters) = tags::parse_query(query);
+    let allowed = if filters.is_empty() {
+        None
+    } else {
+        Some(tags::matching(store, &filters).map_err(|e| format!("Failed to apply filters: {}", e))?)
+    };
+
+    let mut inner = index.inner.lock().unwrap_or_else(|e| e.into_inner());
+    inner
+        .sync(store)
+        .map_err(|e| format!("Failed to refresh search index: {}", e))?;
+
+    Ok(match &allowed {
+        Some(allowed) if text.trim().is_empty() => inner.browse(source, after, allowed, k),
+        _ => inner.search(&text, source, after, allowed.as_ref(), k),
+    })
+}
+
+#[tauri::command]
+pub async fn search_items(
+    store: State<'_, Store>,
+    index: State<'_, SearchIndex>,
+    query: String,
+    mode: Option<String>,
+    after: Option<i64>,
+    k: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    let k = k.unwrap_or(DEFAULT_K).clamp(1, MAX_K);
+    run(&store, &index, &query, mode.as_deref(), after, k)
+}
+
+/// Tag every item a search finds, not just the first page
+#[tauri::command]
+pub async fn tag_search_results(
+    store: State<'_, Store>,
+    index: State<'_, SearchIndex>,
+    query: String,
+    mode: Option<String>,
+    after: Option<i64>,
+    tags: Vec<String>,
+) -> Result<TagReport, String> {
+    let tag_names = tags
+        .iter()
+        .map(|t| tags::normalize_tag(t))
+        .collect::<Result<Vec<_>, _>>()?;
+    let ids: Vec<i64> = run(&store, &index, &query, mode.as_deref(), after, usize::MAX)?
+        .iter()
+        .map(|r| r.item.id)
+        .collect();
+
+    let tagged = tags::add(&store, &ids, &tag_names).map_err(|e| format!("Failed to tag items: {}", e))?;
+    Ok(TagReport {
+        matched: ids.len(),
+        tagged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i64, text: &str, source: &str, created_ts: i64) -> Item {
+        Item {
+            id,
+            text: text.to_string(),
+            content_hash: String::new(),
+            source: source.to_string(),
+            blob_uri: None,
+            created_ts,
+            readable_time: String::new(),
+        }
+    }
+
+    fn index(items: Vec<Item>) -> Inner {
+        let mut inner = Inner::default();
+        for item in items {
+            inner.add(item);
+        }
+        inner
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<i64> {
+        results.iter().map(|r| r.item.id).collect()
+    }
+
+    #[test]
+    fn bm25_ranks_denser_matches_first() {
+        let now = now_ts();
+        let inner = index(vec![
+            item(1, "invoice for the office chairs and the office desks", "clipboard", now),
+            item(2, "invoice invoice invoice overdue", "clipboard", now),
+            item(3, "lunch order for friday", "clipboard", now),
+        ]);
+        assert_eq!(ids(&inner.search("invoice", None, None, None, 10)), [2, 1]);
+        // Every term has to match
+        assert_eq!(ids(&inner.search("invoice office", None, None, None, 10)), [1]);
+        assert!(inner.search("dinner", None, None, None, 10).is_empty());
+    }
+
+    #[test]
+    fn recency_breaks_ties() {
+        let now = now_ts();
+        let inner = index(vec![
+            item(1, "meeting notes", "clipboard", now - 30 * 86400),
+            item(2, "meeting notes", "clipboard", now),
+            item(3, "meeting notes", "clipboard", now - 86400),
+        ]);
+        let results = inner.search("meeting", None, None, None, 10);
+        assert_eq!(ids(&results), [2, 3, 1]);
+        // Brand new is worth up to 1.5x, a month old hardly anything extra
+        assert!(results[0].score / results[2].score > 1.4);
+    }
+
+    #[test]
+    fn prefix_and_typo_matches() {
+        let now = now_ts();
+        let inner = index(vec![
+            item(1, "kubernetes deployment", "clipboard", now),
+            item(2, "deploy script", "clipboard", now),
+            item(3, "cat picture", "clipboard", now),
+        ]);
+        // Exact beats prefix
+        assert_eq!(ids(&inner.search("deploy", None, None, None, 10)), [2, 1]);
+        assert_eq!(ids(&inner.search("kuber", None, None, None, 10)), [1]);
+        // Two typos in a long word, one in a short one
+        assert_eq!(ids(&inner.search("kubernetis deplyment", None, None, None, 10)), [1]);
+        assert_eq!(ids(&inner.search("scrpt", None, None, None, 10)), [2]);
+        // Short words have to be spelled right
+        assert!(inner.search("cot", None, None, None, 10).is_empty());
+    }
+
+    #[test]
+    fn levenshtein_bounds() {
+        assert!(levenshtein_within("kitten", "sitting", 3));
+        assert!(!levenshtein_within("kitten", "sitting", 2));
+        assert!(levenshtein_within("über", "uber", 1));
+        assert!(levenshtein_within("same", "same", 0));
+    }
+
+    #[test]
+    fn mode_filters_by_source() {
+        let now = now_ts();
+        let inner = index(vec![
+            item(1, "receipt from the store", "clipboard", now),
+            item(2, "receipt from the store", "screenshot", now),
+        ]);
+        let by_mode = |mode| {
+            let mut found = ids(&inner.search("receipt", mode_source(mode).unwrap(), None, None, 10));
+            found.sort();
+            found
+        };
+        assert_eq!(by_mode("all"), [1, 2]);
+        assert_eq!(by_mode("text"), [1, 2]);
+        assert_eq!(by_mode("images"), [2]);
+        assert_eq!(by_mode("clipboard"), [1]);
+        assert!(mode_source("video").is_err());
+    }
+
+    #[test]
+    fn after_is_inclusive() {
+        let inner = index(vec![
+            item(1, "release notes", "clipboard", 1_000),
+            item(2, "release notes", "clipboard", 2_000),
+            item(3, "release notes", "clipboard", 3_000),
+        ]);
+        let mut found = ids(&inner.search("release", None, Some(2_000), None, 10));
+        found.sort();
+        assert_eq!(found, [2, 3]);
+
+        let allowed = HashSet::from([1, 2, 3]);
+        assert_eq!(ids(&inner.browse(None, Some(2_000), &allowed, 10)), [3, 2]);
+    }
+
+    #[test]
+    fn placeholders_and_removed_items_dont_match() {
+        let now = now_ts();
+        let mut inner = index(vec![
+            item(1, fingerprint::OCR_PENDING, "screenshot", now),
+            item(2, "pending invoice", "clipboard", now),
+        ]);
+        assert_eq!(ids(&inner.search("pending", None, None, None, 10)), [2]);
+
+        inner.remove(2);
+        assert!(inner.search("pending", None, None, None, 10).is_empty());
+        assert_eq!(inner.total_len, 0);
+    }
+}
@@ -0,0 +1,864 @@
+// App settings, one TOML file (settings.toml) in the app config dir.
+// Replaces the per-module JSON files (hotkeys.json, sensitive.json, ...), those are
+// imported once when settings.toml doesn't exist yet. The file is watched, so hand
+// edits apply without a restart. Every accepted change emits settings://changed,
+// a hand edit that doesn't validate emits settings://invalid and is ignored
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use globset::Glob;
+use notify::{Event, RecursiveMode, Watcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, LogicalSize, Manager, State};
+use tauri_plugin_global_shortcut::Shortcut;
+
+use crate::autopaste::{AutoPaste, AutoPasteConfig};
+use crate::fingerprint::FingerprintConfig;
+use crate::hotkeys::{self, HotkeyConfig, Hotkeys};
+use crate::image_sandbox::SandboxConfig;
+use crate::retention::RetentionSettings;
+use crate::screenshot_watcher::WatcherConfig;
+use crate::sensitive::{Detector, SensitiveConfig};
+use crate::tags::{self, TagSettings, Tagger};
+use crate::vault::VaultSettings;
+use crate::{config, quickboard};
+
+pub const CHANGED_EVENT: &str = "settings://changed";
+pub const INVALID_EVENT: &str = "settings://invalid";
+
+const FILE: &str = "settings.toml";
+
+/// Bump when a field is renamed or changes meaning, and teach `migrate` about it
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Edits usually come as several write events in a row
+const RELOAD_DEBOUNCE_MS: u64 = 300;
+
+// Sections that are only read at startup, search by the Python backend
+const RESTART_SECTIONS: &[&str] = &["screenshots", "images", "search"];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub main: WindowSize,
+    pub quickboard: WindowSize,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        // Same as tauri.conf.json
+        WindowSettings {
+            main: WindowSize {
+                width: 820,
+                height: 560,
+            },
+            quickboard: WindowSize {
+                width: 700,
+                height: 400,
+            },
+        }
+    }
+}
+
+/// Read by app/core/config.py, see CLIPMIND_SETTINGS in backend.rs
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub top_k_results: u32,
+    pub embedding_model: String,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        SearchSettings {
+            top_k_results: 5,
+            embedding_model: "all-MiniLM-L6-v2".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub schema_version: u32,
+    pub windows: WindowSettings,
+    pub hotkeys: HotkeyConfig,
+    pub autopaste: AutoPasteConfig,
+    pub sensitive: SensitiveConfig,
+    pub fingerprint: FingerprintConfig,
+    pub screenshots: WatcherConfig,
+    pub images: SandboxConfig,
+    pub search: SearchSettings,
+    pub tags: TagSettings,
+    pub retention: RetentionSettings,
+    pub vault: VaultSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            schema_version: SCHEMA_VERSION,
+            windows: WindowSettings::default(),
+            hotkeys: HotkeyConfig::default(),
+            autopaste: AutoPasteConfig::default(),
+            sensitive: SensitiveConfig::default(),
+            fingerprint: FingerprintConfig::default(),
+            screenshots: WatcherConfig::default(),
+            images: SandboxConfig::default(),
+            search: SearchSettings::default(),
+            tags: TagSettings::default(),
+            retention: RetentionSettings::default(),
+            vault: VaultSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct FieldError {
+    /// Dotted path, e.g. "windows.quickboard.width"
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
+pub enum SettingsError {
+    Invalid(Vec<FieldError>),
+    Io(String),
+}
+
+impl fmt::Debug for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Invalid(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| format!("{:?}", e)).collect();
+                write!(f, "Invalid settings: {}", errors.join("; "))
+            }
+            SettingsError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+#[derive(Clone, Serialize)]
+pub struct SettingsChanged {
+    pub settings: Settings,
+    /// Top-level sections that differ from before
+    pub changed: Vec<String>,
+    /// Changed sections that only take effect after a restart
+    pub restart_required: Vec<String>,
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    current: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    pub fn get(&self) -> Settings {
+        self.current.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Where settings.toml lives, for the Python side
+pub fn path(app: &AppHandle) -> Option<PathBuf> {
+    app.try_state::<SettingsStore>().map(|store| store.path.clone())
+}
+
+/// Current settings, defaults before `start` ran
+pub fn get(app: &AppHandle) -> Settings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get())
+        .unwrap_or_default()
+}
+
+struct Errors(Vec<FieldError>);
+
+impl Errors {
+    fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn range<T: PartialOrd + fmt::Display>(&mut self, field: &str, value: T, min: T, max: T) {
+        if value < min || value > max {
+            self.add(field, format!("must be between {} and {}", min, max));
+        }
+    }
+
+    fn absolute(&mut self, field: String, path: &Path) {
+        if !path.is_absolute() {
+            self.add(field, "must be an absolute path");
+        }
+    }
+}
+
+pub fn validate(settings: &Settings) -> Vec<FieldError> {
+    let mut errors = Errors(Vec::new());
+
+    if settings.schema_version > SCHEMA_VERSION {
+        errors.add(
+            "schema_version",
+            format!("newer than this version of ClipMind understands ({})", SCHEMA_VERSION),
+        );
+    }
+
+    for (name, size) in [("main", &settings.windows.main), ("quickboard", &settings.windows.quickboard)] {
+        errors.range(&format!("windows.{}.width", name), size.width, 200, 8000);
+        errors.range(&format!("windows.{}.height", name), size.height, 150, 8000);
+    }
+
+    let mut seen = Vec::new();
+    for (action, accelerator) in &settings.hotkeys.bindings {
+        let field = format!("hotkeys.bindings.{}", action.key());
+        match Shortcut::from_str(accelerator) {
+            Err(e) => errors.add(field, e.to_string()),
+            Ok(shortcut) if seen.contains(&shortcut.id()) => errors.add(field, "already bound to another action"),
+            Ok(shortcut) => seen.push(shortcut.id()),
+        }
+    }
+
+    errors.range("autopaste.delay_ms", settings.autopaste.delay_ms, 0, 2000);
+    errors.range("sensitive.expire_after_secs", settings.sensitive.expire_after_secs, 10, 30 * 24 * 3600);
+    errors.range("fingerprint.max_distance", settings.fingerprint.max_distance, 0, 64);
+    errors.range("fingerprint.compare_recent", settings.fingerprint.compare_recent, 1, 10_000);
+
+    let screenshots = &settings.screenshots;
+    errors.range("screenshots.debounce_ms", screenshots.debounce_ms, 100, 60_000);
+    for (i, root) in screenshots.roots.iter().enumerate() {
+        errors.absolute(format!("screenshots.roots[{}].path", i), &root.path);
+    }
+    for (name, globs) in [("include", &screenshots.include), ("exclude", &screenshots.exclude)] {
+        for (i, pattern) in globs.iter().enumerate() {
+            if let Err(e) = Glob::new(pattern) {
+                errors.add(format!("screenshots.{}[{}]", name, i), e.kind().to_string());
+            }
+        }
+    }
+
+    let images = &settings.images;
+    for (i, root) in images.extra_roots.iter().enumerate() {
+        errors.absolute(format!("images.extra_roots[{}]", i), root);
+    }
+    errors.range("images.max_file_bytes", images.max_file_bytes, 1024 * 1024, 4 * 1024 * 1024 * 1024);
+    errors.range("images.max_pixels", images.max_pixels, 1_000_000, 1_000_000_000);
+
+    for (i, rule) in settings.tags.rules.iter().enumerate() {
+        if let Err(e) = tags::normalize_tag(&rule.tag) {
+            errors.add(format!("tags.rules[{}].tag", i), e);
+        }
+        if let Some(Err(e)) = rule.pattern.as_deref().map(Regex::new) {
+            errors.add(format!("tags.rules[{}].pattern", i), e.to_string());
+        }
+        if rule.source.as_deref().is_some_and(|s| s != "clipboard" && s != "screenshot") {
+            errors.add(format!("tags.rules[{}].source", i), "must be clipboard or screenshot");
+        }
+        if rule.pattern.is_none() && rule.source.is_none() {
+            errors.add(format!("tags.rules[{}]", i), "needs a pattern, a source or both");
+        }
+    }
+
+    let retention = &settings.retention;
+    errors.range("retention.sweep_interval_mins", retention.sweep_interval_mins, 5, 7 * 24 * 60);
+    for (source, rule) in &retention.rules {
+        let field = format!("retention.rules.{}", source);
+        if source != "clipboard" && source != "screenshot" {
+            errors.add(field.clone(), "must be clipboard or screenshot");
+        }
+        if rule.max_age_days == Some(0) {
+            errors.add(format!("{}.max_age_days", field), "must be at least 1");
+        }
+        if rule.max_items == Some(0) {
+            errors.add(format!("{}.max_items", field), "must be at least 1");
+        }
+        if rule.max_bytes == Some(0) {
+            errors.add(format!("{}.max_bytes", field), "must be at least 1");
+        }
+    }
+
+    errors.range("vault.auto_lock_mins", settings.vault.auto_lock_mins, 0, 24 * 60);
+
+    // Same bounds as /search accepts
+    errors.range("search.top_k_results", settings.search.top_k_results, 1, 100);
+    if settings.search.embedding_model.trim().is_empty() {
+        errors.add("search.embedding_model", "can't be empty");
+    }
+
+    errors.0
+}
+
+/// Bring an older settings file up to SCHEMA_VERSION.
+/// Files without a version are from before versioning, same layout as 1
+fn migrate(mut table: toml::Table) -> (toml::Table, bool) {
+    let version = table
+        .get("schema_version")
+        .and_then(toml::Value::as_integer)
+        .unwrap_or(0);
+    if version >= SCHEMA_VERSION as i64 {
+        return (table, false);
+    }
+
+    // Future migrations go here, one step per version
+
+    table.insert("schema_version".into(), toml::Value::Integer(SCHEMA_VERSION as i64));
+    (table, true)
+}
+
+fn parse(raw: &str) -> Result<(Settings, bool), SettingsError> {
+    let table: toml::Table = toml::from_str(raw).map_err(|e| {
+        SettingsError::Invalid(vec![FieldError {
+            field: String::new(),
+            message: e.message().to_string(),
+        }])
+    })?;
+    let (table, migrated) = migrate(table);
+
+    let settings = serde_path_to_error::deserialize(toml::Value::Table(table)).map_err(|e| {
+        SettingsError::Invalid(vec![FieldError {
+            field: e.path().to_string(),
+            message: e.inner().to_string(),
+        }])
+    })?;
+    Ok((settings, migrated))
+}
+
+fn save(path: &Path, settings: &Settings) -> Result<(), SettingsError> {
+    let raw = toml::to_string_pretty(settings).map_err(|e| SettingsError::Io(format!("Failed to serialize settings: {}", e)))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| SettingsError::Io(format!("Failed to create config dir: {}", e)))?;
+    }
+
+    // The watcher must never see half a file
+    let tmp = path.with_extension("toml.tmp");
+    fs::write(&tmp, raw).map_err(|e| SettingsError::Io(format!("Failed to write {}: {}", tmp.display(), e)))?;
+    fs::rename(&tmp, path).map_err(|e| SettingsError::Io(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Settings from the old JSON files, for the first run with settings.toml
+fn import_legacy(app: &AppHandle) -> Settings {
+    Settings {
+        hotkeys: config::load(app, "hotkeys.json"),
+        autopaste: config::load(app, "autopaste.json"),
+        sensitive: config::load(app, "sensitive.json"),
+        fingerprint: config::load(app, "fingerprint.json"),
+        screenshots: config::load(app, "screenshots.json"),
+        images: config::load(app, "images.json"),
+        ..Settings::default()
+    }
+}
+
+fn load(app: &AppHandle, path: &Path) -> Settings {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(_) => {
+            let settings = import_legacy(app);
+            match save(path, &settings) {
+                Ok(()) => eprintln!("[SETTINGS] Created {}", path.display()),
+                Err(e) => eprintln!("[SETTINGS] {}", e),
+            }
+            return settings;
+        }
+    };
+
+    match parse(&raw) {
+        Ok((settings, migrated)) => {
+            let errors = validate(&settings);
+            for error in &errors {
+                eprintln!("[SETTINGS] Using the default for {:?}", error);
+            }
+            // Same rules as `update`, only the broken fields are dropped and the file stays as it is
+            let settings = if errors.is_empty() {
+                settings
+            } else {
+                with_defaults(&settings, &errors)
+            };
+            if migrated {
+                if let Err(e) = save(path, &settings) {
+                    eprintln!("[SETTINGS] Failed to save migrated settings: {}", e);
+                }
+            }
+            settings
+        }
+        // Leave the file alone so it can be fixed by hand
+        Err(e) => {
+            eprintln!("[SETTINGS] Ignoring {}: {}", path.display(), e);
+            Settings::default()
+        }
+    }
+}
+
+/// `settings` with every field in `errors` put back to its default.
+/// A bad list entry resets the whole list, a bad map entry is dropped. Falls back
+/// to all defaults when that still doesn't validate
+fn with_defaults(settings: &Settings, errors: &[FieldError]) -> Settings {
+    let (Ok(mut value), Ok(defaults)) = (serde_json::to_value(settings), serde_json::to_value(Settings::default())) else {
+        return Settings::default();
+    };
+
+    for error in errors {
+        // "screenshots.roots[2].path" -> ["screenshots", "roots"]
+        let field = error.field.split('[').next().unwrap_or_default();
+        let keys: Vec<&str> = field.split('.').filter(|key| !key.is_empty()).collect();
+        let Some((last, parents)) = keys.split_last() else {
+            return Settings::default();
+        };
+
+        let parent = parents.iter().try_fold(&mut value, |value, key| value.get_mut(*key));
+        let default = keys.iter().try_fold(&defaults, |value, key| value.get(*key));
+        if let Some(Value::Object(parent)) = parent {
+            match default {
+                Some(default) => parent.insert(last.to_string(), default.clone()),
+                None => parent.remove(*last),
+            };
+        }
+    }
+
+    match serde_json::from_value::<Settings>(value) {
+        Ok(fixed) if validate(&fixed).is_empty() => fixed,
+        _ => Settings::default(),
+    }
+}
+
+/// Top-level keys whose values differ
+fn changed_sections(old: &Settings, new: &Settings) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+fn resize(app: &AppHandle, label: &str, size: &WindowSize) {
+    if let Some(window) = app.get_webview_window(label) {
+        let _ = window.set_size(LogicalSize::new(size.width, size.height));
+    }
+}
+
+/// Push changed sections out to the parts of the app that keep their own copy
+fn apply(app: &AppHandle, settings: &Settings, changed: &[String]) {
+    for section in changed {
+        match section.as_str() {
+            "windows" => {
+                resize(app, "main", &settings.windows.main);
+                resize(app, quickboard::LABEL, &settings.windows.quickboard);
+            }
+            // set_hotkey has already registered them when it's the one saving
+            "hotkeys" if app.try_state::<Hotkeys>().is_some_and(|h| h.config() != settings.hotkeys) => {
+                hotkeys::apply(app, settings.hotkeys.clone());
+            }
+            "autopaste" => {
+                if let Some(autopaste) = app.try_state::<AutoPaste>() {
+                    autopaste.set_config(settings.autopaste.clone());
+                }
+            }
+            "sensitive" => {
+                if let Some(detector) = app.try_state::<Detector>() {
+                    detector.set_config(settings.sensitive.clone());
+                }
+            }
+            "tags" => {
+                if let Some(tagger) = app.try_state::<Tagger>() {
+                    tagger.set_config(&settings.tags);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Swap in `new` (already validated and saved), apply it and tell the webviews
+fn replace(app: &AppHandle, store: &SettingsStore, new: Settings) -> Settings {
+    let old = {
+        let mut current = store.current.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, new.clone())
+    };
+    announce(app, &old, new)
+}
+
+/// Apply what changed from `old` to `new` and tell the webviews
+fn announce(app: &AppHandle, old: &Settings, new: Settings) -> Settings {
+    let changed = changed_sections(old, &new);
+    if changed.is_empty() {
+        return new;
+    }
+    apply(app, &new, &changed);
+
+    let restart_required: Vec<String> = changed
+        .iter()
+        .filter(|s| RESTART_SECTIONS.contains(&s.as_str()))
+        .cloned()
+        .collect();
+    if !restart_required.is_empty() {
+        eprintln!("[SETTINGS] {} changes apply after a restart", restart_required.join(", "));
+    }
+
+    let _ = app.emit(
+        CHANGED_EVENT,
+        SettingsChanged {
+            settings: new.clone(),
+            changed,
+            restart_required,
+        },
+    );
+    new
+}
+
+/// Change settings through `f`, then validate, save and apply them
+pub fn update(
+    app: &AppHandle,
+    f: impl FnOnce(&mut Settings) -> Result<(), SettingsError>,
+) -> Result<Settings, SettingsError> {
+    let store = app.state::<SettingsStore>();
+    // Held until the new settings are saved and in place, two updates at once would
+    // otherwise both start from the same settings and the first one would be lost
+    let mut current = store.current.lock().unwrap_or_else(|e| e.into_inner());
+    let mut new = current.clone();
+    f(&mut new)?;
+
+    let errors = validate(&new);
+    if !errors.is_empty() {
+        return Err(SettingsError::Invalid(errors));
+    }
+    save(&store.path, &new)?;
+    let old = std::mem::replace(&mut *current, new.clone());
+    drop(current);
+
+    Ok(announce(app, &old, new))
+}
+
+/// Pick up hand edits of settings.toml
+fn reload(app: &AppHandle) {
+    let store = app.state::<SettingsStore>();
+    let Ok(raw) = fs::read_to_string(&store.path) else {
+        return;
+    };
+
+    let errors = match parse(&raw) {
+        Ok((settings, _)) => {
+            let errors = validate(&settings);
+            if errors.is_empty() {
+                // Our own saves come back through here too, replace skips those
+                replace(app, &store, settings);
+                return;
+            }
+            errors
+        }
+        Err(SettingsError::Invalid(errors)) => errors,
+        Err(SettingsError::Io(e)) => vec![FieldError {
+            field: String::new(),
+            message: e,
+        }],
+    };
+
+    for error in &errors {
+        eprintln!("[SETTINGS] Ignoring edit, {:?}", error);
+    }
+    let _ = app.emit(INVALID_EVENT, errors);
+}
+
+fn watch(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let dir = path.parent().ok_or("Settings file has no parent dir")?.to_path_buf();
+    let file_name = path.file_name().map(|n| n.to_os_string());
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| format!("Failed to create watcher: {}", e))?;
+    // The dir, not the file, saving through a rename replaces the file's inode
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+    let app = app.clone();
+    thread::Builder::new()
+        .name("settings-watcher".into())
+        .spawn(move || {
+            let _watcher = watcher;
+            let mut dirty = false;
+            loop {
+                match rx.recv_timeout(Duration::from_millis(RELOAD_DEBOUNCE_MS)) {
+                    Ok(Ok(event)) => {
+                        if event.paths.iter().any(|p| p.file_name() == file_name.as_deref()) {
+                            dirty = true;
+                        }
+                    }
+                    Ok(Err(e)) => eprintln!("[SETTINGS] Watch error: {}", e),
+                    Err(RecvTimeoutError::Timeout) if dirty => {
+                        dirty = false;
+                        reload(&app);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start settings watcher: {}", e))?;
+    Ok(())
+}
+
+/// Load settings.toml (creating it from the old JSON files on first run) and watch it.
+/// Runs before everything that reads settings
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    let path = config::config_path(app, FILE)?;
+    let settings = load(app, &path);
+
+    resize(app, "main", &settings.windows.main);
+    resize(app, quickboard::LABEL, &settings.windows.quickboard);
+
+    app.manage(SettingsStore {
+        path: path.clone(),
+        current: Mutex::new(settings),
+    });
+
+    // Without hot reload the app still works, edits just need a restart
+    if let Err(e) = watch(app, &path) {
+        eprintln!("[SETTINGS] {}", e);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings(store: State<'_, SettingsStore>) -> Settings {
+    store.get()
+}
+
+/// Paths of every leaf in `value`, e.g. "windows.main.width"
+fn leaf_paths(value: &Value, prefix: &str, out: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                leaf_paths(value, &path, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string());
+        }
+    }
+}
+
+/// Recursive merge, objects are merged key by key, everything else is replaced
+fn merge(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+/// Merge a partial update into `settings`, rejecting keys that aren't settings
+fn apply_patch(settings: &mut Settings, patch: Value) -> Result<(), SettingsError> {
+    let mut value = serde_json::to_value(&*settings).map_err(|e| SettingsError::Io(e.to_string()))?;
+    let mut requested = BTreeSet::new();
+    leaf_paths(&patch, "", &mut requested);
+    merge(&mut value, patch);
+
+    *settings = serde_path_to_error::deserialize(value).map_err(|e| {
+        SettingsError::Invalid(vec![FieldError {
+            field: e.path().to_string(),
+            message: e.inner().to_string(),
+        }])
+    })?;
+
+    // serde(default) quietly drops keys it doesn't know, a typo shouldn't look like it worked
+    let mut known = BTreeSet::new();
+    leaf_paths(
+        &serde_json::to_value(&*settings).map_err(|e| SettingsError::Io(e.to_string()))?,
+        "",
+        &mut known,
+    );
+    let unknown: Vec<FieldError> = requested
+        .into_iter()
+        .filter(|path| !known.iter().any(|k| k == path || k.starts_with(&format!("{}.", path))))
+        .map(|field| FieldError {
+            field,
+            message: "unknown setting".to_string(),
+        })
+        .collect();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(SettingsError::Invalid(unknown))
+    }
+}
+
+/// Apply a partial update, e.g. `{ "windows": { "quickboard": { "width": 800 } } }`.
+/// Every problem comes back with the field it's about
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: Value) -> Result<Settings, SettingsError> {
+    update(&app, |settings| apply_patch(settings, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    fn invalid_fields(result: Result<(), SettingsError>) -> Vec<String> {
+        match result {
+            Err(SettingsError::Invalid(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            Err(SettingsError::Io(e)) => panic!("unexpected io error: {}", e),
+            Ok(()) => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parse_fills_in_defaults_and_migrates() {
+        let (settings, migrated) = parse("[windows.quickboard]\nwidth = 900\nheight = 500\n").unwrap();
+        assert!(migrated);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert_eq!(settings.windows.quickboard.width, 900);
+        assert_eq!(settings.windows.main.width, 820);
+        assert_eq!(settings.search.top_k_results, 5);
+
+        let (_, migrated) = parse(&format!("schema_version = {}\n", SCHEMA_VERSION)).unwrap();
+        assert!(!migrated);
+    }
+
+    #[test]
+    fn parse_reports_the_broken_field() {
+        let Err(SettingsError::Invalid(errors)) = parse("[windows.main]\nwidth = \"wide\"\nheight = 500\n") else {
+            panic!("a string width should not parse");
+        };
+        assert_eq!(fields(&errors), ["windows.main.width"]);
+
+        let Err(SettingsError::Invalid(errors)) = parse("windows = [") else {
+            panic!("broken toml should not parse");
+        };
+        assert_eq!(fields(&errors), [""]);
+    }
+
+    #[test]
+    fn validate_flags_each_bad_field() {
+        assert!(validate(&Settings::default()).is_empty());
+
+        let (settings, _) = parse(
+            "schema_version = 99
+             [windows.quickboard]
+             width = 50
+             height = 400
+             [search]
+             top_k_results = 0
+             [retention.rules.email]
+             max_items = 10",
+        )
+        .unwrap();
+        assert_eq!(
+            fields(&validate(&settings)),
+            [
+                "schema_version",
+                "windows.quickboard.width",
+                "retention.rules.email",
+                "search.top_k_results"
+            ]
+        );
+    }
+
+    #[test]
+    fn with_defaults_only_resets_invalid_fields() {
+        let (settings, _) = parse(
+            "[windows.main]
+             width = 1000
+             height = 700
+             [windows.quickboard]
+             width = 50
+             height = 400
+             [screenshots]
+             roots = [{ path = \"/home/me/Pictures\" }, { path = \"relative/dir\" }]
+             [retention.rules.email]
+             max_items = 10
+             [retention.rules.clipboard]
+             max_age_days = 0
+             max_items = 500",
+        )
+        .unwrap();
+        let errors = validate(&settings);
+        let fixed = with_defaults(&settings, &errors);
+
+        assert!(validate(&fixed).is_empty());
+        assert_eq!(fixed.windows.main.width, 1000);
+        assert_eq!(fixed.windows.quickboard.width, 700);
+        assert_eq!(fixed.windows.quickboard.height, 400);
+        // A bad entry resets the whole list
+        assert!(fixed.screenshots.roots.is_empty());
+        // A bad map entry is dropped, a bad field in a good one goes back to its default
+        assert!(!fixed.retention.rules.contains_key("email"));
+        let clipboard = &fixed.retention.rules["clipboard"];
+        assert_eq!((clipboard.max_age_days, clipboard.max_items), (None, Some(500)));
+    }
+
+    #[test]
+    fn with_defaults_gives_up_on_unknown_fields() {
+        let mut settings = Settings::default();
+        settings.windows.main.width = 1000;
+        let errors = [FieldError {
+            field: String::new(),
+            message: "broken".to_string(),
+        }];
+        assert_eq!(with_defaults(&settings, &errors).windows.main.width, 820);
+    }
+
+    #[test]
+    fn patch_updates_nested_fields() {
+        let mut settings = Settings::default();
+        apply_patch(&mut settings, json!({ "windows": { "quickboard": { "width": 800 } } })).unwrap();
+        assert_eq!(settings.windows.quickboard.width, 800);
+        assert_eq!(settings.windows.quickboard.height, 400);
+
+        // Map entries the defaults don't have are fine
+        apply_patch(&mut settings, json!({ "retention": { "rules": { "clipboard": { "max_items": 100 } } } })).unwrap();
+        assert_eq!(settings.retention.rules["clipboard"].max_items, Some(100));
+    }
+
+    #[test]
+    fn patch_rejects_unknown_keys() {
+        let mut settings = Settings::default();
+        assert_eq!(
+            invalid_fields(apply_patch(
+                &mut settings,
+                json!({ "windows": { "quickboard": { "widht": 800 } }, "themes": { "dark": true } })
+            )),
+            ["themes.dark", "windows.quickboard.widht"]
+        );
+        assert_eq!(
+            invalid_fields(apply_patch(&mut settings, json!({ "search": { "top_k_results": "ten" } }))),
+            ["search.top_k_results"]
+        );
+    }
+}
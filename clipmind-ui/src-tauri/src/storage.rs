@@ -0,0 +1,353 @@
+// Rust side of clipmind.db
+// Reads and writes the same `item` table the SQLModel `Item` in app/db/models.py
+// creates, so the desktop app and the Python backend can share one database
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, TimeZone};
+use rusqlite::{ffi, params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use zeroize::Zeroizing;
+
+const DB_FILE: &str = "clipmind.db";
+
+// Same limits as /items/recent
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 100;
+
+// Matches the DDL SQLModel emits, so a fresh file also works for the Python side.
+// AUTOINCREMENT so a deleted item's id never comes back, the FAISS index and
+// index_tombstone only know items by id
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    text VARCHAR NOT NULL,
+    content_hash VARCHAR NOT NULL,
+    source VARCHAR NOT NULL,
+    blob_uri VARCHAR,
+    created_ts INTEGER NOT NULL,
+    readable_time VARCHAR NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ix_item_content_hash ON item (content_hash);
+CREATE INDEX IF NOT EXISTS ix_item_source ON item (source);
+";
+
+pub const ITEM_COLUMNS: &str = "id, text, content_hash, source, blob_uri, created_ts, readable_time";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub id: i64,
+    pub text: String,
+    pub content_hash: String,
+    pub source: String,
+    pub blob_uri: Option<String>,
+    pub created_ts: i64,
+    pub readable_time: String,
+}
+
+impl Item {
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Item {
+            id: row.get("id")?,
+            text: row.get("text")?,
+            // Rows from before migrate_add_hash.py can still have NULL here
+            content_hash: row
+                .get::<_, Option<String>>("content_hash")?
+                .unwrap_or_default(),
+            source: row.get("source")?,
+            blob_uri: row.get("blob_uri")?,
+            created_ts: row.get("created_ts")?,
+            readable_time: row.get("readable_time")?,
+        })
+    }
+}
+
+pub struct NewItem<'a> {
+    pub text: &'a str,
+    pub content_hash: &'a str,
+    pub source: &'a str,
+    pub blob_uri: Option<&'a str>,
+    pub created_ts: i64,
+}
+
+/// Open the db, with the raw SQLCipher key when it's encrypted (see vault.rs)
+fn connect(path: &Path, key: Option<&[u8]>) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    if let Some(key) = key {
+        // Has to come before anything reads the file. A raw x'..' key skips SQLCipher's own KDF
+        let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+        let pragma = Zeroizing::new(format!("PRAGMA key = \"x'{}'\";", Zeroizing::new(hex).as_str()));
+        conn.execute_batch(&pragma)?;
+    }
+    // The Python watchers write to the same file, wait for them instead of failing
+    conn.busy_timeout(Duration::from_secs(5))?;
+    // First real read, fails with NotADatabase when the key is wrong
+    conn.execute_batch(SCHEMA)?;
+    migrate_autoincrement(&conn)?;
+    Ok(conn)
+}
+
+/// Rebuild an item table from before AUTOINCREMENT. SQLite can't add it in place.
+/// The sequence starts past every tombstoned id too, those vectors may still be in FAISS.
+/// Triggers go with the old table, search::start creates its own again
+fn migrate_autoincrement(conn: &Connection) -> rusqlite::Result<()> {
+    let sql: String = conn.query_row("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'item'", [], |row| {
+        row.get(0)
+    })?;
+    if sql.to_uppercase().contains("AUTOINCREMENT") {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(&format!(
+        "ALTER TABLE item RENAME TO item_old;
+         DROP INDEX IF EXISTS ix_item_content_hash;
+         DROP INDEX IF EXISTS ix_item_source;
+         {SCHEMA}
+         INSERT INTO item ({ITEM_COLUMNS}) SELECT {ITEM_COLUMNS} FROM item_old;
+         DROP TABLE item_old;"
+    ))?;
+
+    let tombstones: bool = tx.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'index_tombstone')",
+        [],
+        |row| row.get(0),
+    )?;
+    let last_tombstone: Option<i64> = if tombstones {
+        tx.query_row("SELECT MAX(item_id) FROM index_tombstone", [], |row| row.get(0))?
+    } else {
+        None
+    };
+    if let Some(last) = last_tombstone {
+        tx.execute(
+            "UPDATE sqlite_sequence SET seq = MAX(seq, ?1) WHERE name = 'item'",
+            [last],
+        )?;
+        tx.execute(
+            "INSERT INTO sqlite_sequence (name, seq) SELECT 'item', ?1
+             WHERE NOT EXISTS (SELECT 1 FROM sqlite_sequence WHERE name = 'item')",
+            [last],
+        )?;
+    }
+    tx.commit()?;
+    eprintln!("[STORAGE] Item ids are no longer reused");
+    Ok(())
+}
+
+/// What every query returns while the history is locked
+fn locked() -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(ffi::Error::new(ffi::SQLITE_AUTH), Some("History is locked".to_string()))
+}
+
+pub struct Store {
+    path: PathBuf,
+    // None while an encrypted history is locked
+    conn: Mutex<Option<Connection>>,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Ok(Store {
+            path: path.to_path_buf(),
+            conn: Mutex::new(Some(connect(path, None)?)),
+        })
+    }
+
+    /// An encrypted db nothing can read until `reopen` gets the key
+    pub fn locked(path: &Path) -> Self {
+        Store {
+            path: path.to_path_buf(),
+            conn: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Swap in a fresh connection, keyed when `key` is given
+    pub fn reopen(&self, key: Option<&[u8]>) -> rusqlite::Result<()> {
+        let conn = connect(&self.path, key)?;
+        *self.conn.lock().unwrap_or_else(|e| e.into_inner()) = Some(conn);
+        Ok(())
+    }
+
+    /// Close the connection, every query fails until `reopen`
+    pub fn close(&self) {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+
+    /// Run `f` with the connection locked
+    pub fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        match conn.as_ref() {
+            Some(conn) => f(conn),
+            None => Err(locked()),
+        }
+    }
+
+    pub fn recent(&self, limit: u32, source: Option<&str>, after: Option<i64>) -> rusqlite::Result<Vec<Item>> {
+        self.with_conn(|conn| {
+            let sql = format!(
+                "SELECT {} FROM item
+                 WHERE (?1 IS NULL OR source = ?1) AND (?2 IS NULL OR created_ts >= ?2)
+                 ORDER BY created_ts DESC, id DESC
+                 LIMIT ?3",
+                ITEM_COLUMNS
+            );
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let rows = stmt.query_map(params![source, after, limit], Item::from_row)?;
+            rows.collect()
+        })
+    }
+
+    pub fn get(&self, id: i64) -> rusqlite::Result<Option<Item>> {
+        self.with_conn(|conn| {
+            let sql = format!("SELECT {} FROM item WHERE id = ?1", ITEM_COLUMNS);
+            conn.query_row(&sql, [id], Item::from_row).optional()
+        })
+    }
+
+    /// Returns false when there was no item with that id
+    pub fn delete(&self, id: i64) -> rusqlite::Result<bool> {
+        self.with_conn(|conn| Ok(conn.execute("DELETE FROM item WHERE id = ?1", [id])? > 0))
+    }
+
+    /// Every item with an id above `id`, oldest first
+    pub fn items_after_id(&self, id: i64) -> rusqlite::Result<Vec<Item>> {
+        self.with_conn(|conn| {
+            let sql = format!("SELECT {} FROM item WHERE id > ?1 ORDER BY id", ITEM_COLUMNS);
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let rows = stmt.query_map([id], Item::from_row)?;
+            rows.collect()
+        })
+    }
+
+    /// Exact duplicate lookup through ix_item_content_hash
+    pub fn find_by_hash(&self, content_hash: &str) -> rusqlite::Result<Option<i64>> {
+        self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT id FROM item WHERE content_hash = ?1 LIMIT 1",
+                [content_hash],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+    }
+
+    /// Returns false when there was no item with that id
+    pub fn set_blob_uri(&self, id: i64, blob_uri: &str) -> rusqlite::Result<bool> {
+        self.with_conn(|conn| Ok(conn.execute("UPDATE item SET blob_uri = ?1 WHERE id = ?2", params![blob_uri, id])? > 0))
+    }
+
+    pub fn insert(&self, new: NewItem) -> rusqlite::Result<Item> {
+        let readable_time = readable_time(new.created_ts);
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO item (text, content_hash, source, blob_uri, created_ts, readable_time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    new.text,
+                    new.content_hash,
+                    new.source,
+                    new.blob_uri,
+                    new.created_ts,
+                    readable_time
+                ],
+            )?;
+
+            Ok(Item {
+                id: conn.last_insert_rowid(),
+                text: new.text.to_string(),
+                content_hash: new.content_hash.to_string(),
+                source: new.source.to_string(),
+                blob_uri: new.blob_uri.map(str::to_string),
+                created_ts: new.created_ts,
+                readable_time,
+            })
+        })
+    }
+}
+
+pub fn now_ts() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Same "%Y-%m-%d %H:%M:%S" local time string the Python side stores
+pub fn readable_time(ts: i64) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// Find the database the Python backend uses.
+/// CLIPMIND_DB wins, then a clipmind.db in the working dir or any parent
+/// (the backend runs from the repo root), then one in the app data dir
+pub fn locate_db(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    if let Some(path) = env::var_os("CLIPMIND_DB") {
+        return Ok(PathBuf::from(path));
+    }
+
+    if let Ok(cwd) = env::current_dir() {
+        if let Some(found) = cwd.ancestors().map(|dir| dir.join(DB_FILE)).find(|p| p.is_file()) {
+            return Ok(found);
+        }
+    }
+
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(data_dir.join(DB_FILE))
+}
+
+/// Where ClipMind keeps image blobs it owns (as opposed to screenshots it only points at)
+pub fn blob_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    Ok(data_dir.join("blobs"))
+}
+
+#[tauri::command]
+pub async fn list_recent_items(
+    store: State<'_, Store>,
+    limit: Option<u32>,
+    source: Option<String>,
+    after: Option<i64>,
+) -> Result<Vec<Item>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    store
+        .recent(limit, source.as_deref(), after)
+        .map_err(|e| format!("Failed to list items: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_item(store: State<'_, Store>, id: i64) -> Result<Item, String> {
+    store
+        .get(id)
+        .map_err(|e| format!("Failed to load item: {}", e))?
+        .ok_or_else(|| format!("Item {} not found", id))
+}
+
+/// Deletes the item with everything hanging off it, see `retention::purge`
+#[tauri::command]
+pub async fn delete_item(app: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let purged = crate::retention::purge(&app, &[id])?;
+
+    if purged.items == 0 {
+        return Err(format!("Item {} not found", id));
+    }
+    Ok(())
+}
@@ -0,0 +1,683 @@
+// Tags and collections for organizing history.
+// Tags are many-to-many labels, lowercase single words ("work", "code/rust"), added by
+// hand, in bulk from search results, or at capture time by the auto-tag rules in the
+// [tags] settings. Collections are named, hand-picked sets of items.
+// Both can narrow a search: `tag:work collection:"Release notes" deploy`
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::settings;
+use crate::storage::{now_ts, Item, Store, ITEM_COLUMNS};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS tag (
+    id INTEGER NOT NULL PRIMARY KEY,
+    name VARCHAR NOT NULL UNIQUE,
+    created_ts INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS item_tag (
+    item_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (item_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS ix_item_tag_tag_id ON item_tag (tag_id);
+CREATE TABLE IF NOT EXISTS collection (
+    id INTEGER NOT NULL PRIMARY KEY,
+    name VARCHAR NOT NULL UNIQUE COLLATE NOCASE,
+    description VARCHAR NOT NULL DEFAULT '',
+    created_ts INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS collection_item (
+    collection_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    added_ts INTEGER NOT NULL,
+    PRIMARY KEY (collection_id, item_id)
+);
+CREATE INDEX IF NOT EXISTS ix_collection_item_item_id ON collection_item (item_id);
+";
+
+const MAX_TAG_LEN: usize = 64;
+const MAX_COLLECTION_LEN: usize = 100;
+
+/// Tag `tag` on every new item that matches. With both set, both have to match
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutoTagRule {
+    pub tag: String,
+    /// Regex searched for in the item text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// "clipboard" or "screenshot"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagSettings {
+    pub rules: Vec<AutoTagRule>,
+}
+
+#[derive(Serialize)]
+pub struct TagInfo {
+    pub name: String,
+    pub items: u32,
+}
+
+#[derive(Serialize)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub items: u32,
+    pub created_ts: i64,
+}
+
+/// A `tag:` or `collection:` term in a search query
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    Tag(String),
+    Collection(String),
+}
+
+struct CompiledRule {
+    tag: String,
+    pattern: Option<Regex>,
+    source: Option<String>,
+}
+
+impl CompiledRule {
+    fn matches(&self, item: &Item) -> bool {
+        self.source.as_ref().is_none_or(|s| *s == item.source)
+            && self.pattern.as_ref().is_none_or(|re| re.is_match(&item.text))
+    }
+}
+
+/// Auto-tag rules, compiled once per settings change
+#[derive(Default)]
+pub struct Tagger {
+    rules: Mutex<Vec<CompiledRule>>,
+}
+
+impl Tagger {
+    pub fn new(config: &TagSettings) -> Self {
+        let tagger = Tagger::default();
+        tagger.set_config(config);
+        tagger
+    }
+
+    /// Rules that don't compile are skipped, settings::validate reports them
+    pub fn set_config(&self, config: &TagSettings) {
+        let rules = config
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                let tag = normalize_tag(&rule.tag).ok()?;
+                let pattern = match rule.pattern.as_deref().map(Regex::new).transpose() {
+                    Ok(pattern) => pattern,
+                    Err(e) => {
+                        eprintln!("[TAGS] Skipping rule for {}: {}", tag, e);
+                        return None;
+                    }
+                };
+                Some(CompiledRule {
+                    tag,
+                    pattern,
+                    source: rule.source.clone(),
+                })
+            })
+            .collect();
+        *self.rules.lock().unwrap_or_else(|e| e.into_inner()) = rules;
+    }
+
+    fn tags_for(&self, item: &Item) -> Vec<String> {
+        let rules = self.rules.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tags: Vec<String> = rules.iter().filter(|r| r.matches(item)).map(|r| r.tag.clone()).collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+}
+
+/// Lowercase, no whitespace. Letters, digits and - _ . / only
+pub fn normalize_tag(name: &str) -> Result<String, String> {
+    let name = name.trim().trim_start_matches('#').to_lowercase();
+    if name.is_empty() {
+        return Err("Tag name is empty".to_string());
+    }
+    if name.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tag names are at most {} characters", MAX_TAG_LEN));
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_alphanumeric() || "-_./".contains(*c))) {
+        return Err(format!("Tag names can't contain {:?}", c));
+    }
+    Ok(name)
+}
+
+fn normalize_collection(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Collection name is empty".to_string());
+    }
+    if name.chars().count() > MAX_COLLECTION_LEN {
+        return Err(format!("Collection names are at most {} characters", MAX_COLLECTION_LEN));
+    }
+    Ok(name.to_string())
+}
+
+fn normalize_tags(names: &[String]) -> Result<Vec<String>, String> {
+    let mut tags = names.iter().map(|n| normalize_tag(n)).collect::<Result<Vec<_>, _>>()?;
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+/// Split `tag:x` and `collection:x` terms (values may be "quoted") from the rest of the query
+pub fn parse_query(query: &str) -> (String, Vec<Filter>) {
+    let mut text = Vec::new();
+    let mut filters = Vec::new();
+    let mut rest = query.trim_start();
+
+    while !rest.is_empty() {
+        let (word, after) = match rest.find(char::is_whitespace) {
+            Some(end) => rest.split_at(end),
+            None => (rest, ""),
+        };
+        let prefix = ["tag:", "collection:"]
+            .into_iter()
+            .find(|p| word.len() > p.len() && word.get(..p.len()).is_some_and(|w| w.eq_ignore_ascii_case(p)));
+
+        let Some(prefix) = prefix else {
+            text.push(word);
+            rest = after.trim_start();
+            continue;
+        };
+
+        let value_start = &rest[prefix.len()..];
+        let (value, after) = match value_start.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => (&word[prefix.len()..], after),
+        };
+        filters.push(if prefix == "tag:" {
+            // Same spelling rules as tagging, a name that can't be a tag just matches nothing
+            Filter::Tag(normalize_tag(value).unwrap_or_else(|_| value.trim().to_lowercase()))
+        } else {
+            Filter::Collection(value.trim().to_string())
+        });
+        rest = after.trim_start();
+    }
+
+    (text.join(" "), filters)
+}
+
+/// Items matching every filter, an unknown tag or collection matches nothing
+pub fn matching(store: &Store, filters: &[Filter]) -> rusqlite::Result<HashSet<i64>> {
+    store.with_conn(|conn| {
+        let mut result: Option<HashSet<i64>> = None;
+        for filter in filters {
+            let (sql, value) = match filter {
+                Filter::Tag(name) => (
+                    "SELECT it.item_id FROM item_tag it JOIN tag t ON t.id = it.tag_id WHERE t.name = ?1",
+                    name,
+                ),
+                Filter::Collection(name) => (
+                    "SELECT ci.item_id FROM collection_item ci JOIN collection c ON c.id = ci.collection_id
+                     WHERE c.name = ?1",
+                    name,
+                ),
+            };
+            let mut stmt = conn.prepare_cached(sql)?;
+            let ids = stmt.query_map([value], |row| row.get(0))?.collect::<rusqlite::Result<HashSet<i64>>>()?;
+            result = Some(match result {
+                None => ids,
+                Some(prev) => prev.intersection(&ids).copied().collect(),
+            });
+        }
+        Ok(result.unwrap_or_default())
+    })
+}
+
+fn tag_id(conn: &Connection, name: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row("SELECT id FROM tag WHERE name = ?1", [name], |row| row.get(0))
+        .optional()
+}
+
+fn ensure_tag(conn: &Connection, name: &str) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT OR IGNORE INTO tag (name, created_ts) VALUES (?1, ?2)",
+        params![name, now_ts()],
+    )?;
+    conn.query_row("SELECT id FROM tag WHERE name = ?1", [name], |row| row.get(0))
+}
+
+fn collection_id(conn: &Connection, name: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row("SELECT id FROM collection WHERE name = ?1", [name], |row| row.get(0))
+        .optional()
+}
+
+/// Tag every item in `ids` with every tag in `tags`, returns how many links were new
+pub fn add(store: &Store, ids: &[i64], tags: &[String]) -> rusqlite::Result<usize> {
+    store.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let mut added = 0;
+        for tag in tags {
+            let tag_id = ensure_tag(&tx, tag)?;
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO item_tag (item_id, tag_id) SELECT id, ?2 FROM item WHERE id = ?1",
+            )?;
+            for id in ids {
+                added += stmt.execute(params![id, tag_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    })
+}
+
+/// Returns how many links went
+pub fn remove(store: &Store, ids: &[i64], tags: &[String]) -> rusqlite::Result<usize> {
+    store.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let mut removed = 0;
+        for tag in tags {
+            let Some(tag_id) = tag_id(&tx, tag)? else {
+                continue;
+            };
+            let mut stmt = tx.prepare_cached("DELETE FROM item_tag WHERE item_id = ?1 AND tag_id = ?2")?;
+            for id in ids {
+                removed += stmt.execute(params![id, tag_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(removed)
+    })
+}
+
+pub fn for_item(store: &Store, id: i64) -> rusqlite::Result<Vec<String>> {
+    store.with_conn(|conn| {
+        let mut stmt = conn.prepare_cached(
+            "SELECT t.name FROM item_tag it JOIN tag t ON t.id = it.tag_id WHERE it.item_id = ?1 ORDER BY t.name",
+        )?;
+        let rows = stmt.query_map([id], |row| row.get(0))?;
+        rows.collect()
+    })
+}
+
+/// Names of the collections `id` is in
+pub fn collections_for_item(store: &Store, id: i64) -> rusqlite::Result<Vec<String>> {
+    store.with_conn(|conn| {
+        let mut stmt = conn.prepare_cached(
+            "SELECT c.name FROM collection_item ci JOIN collection c ON c.id = ci.collection_id
+             WHERE ci.item_id = ?1 ORDER BY c.name",
+        )?;
+        let rows = stmt.query_map([id], |row| row.get(0))?;
+        rows.collect()
+    })
+}
+
+/// Put `ids` in collection `name`, creating it when it doesn't exist yet.
+/// Returns how many were new
+pub fn collect(store: &Store, name: &str, ids: &[i64]) -> Result<usize, String> {
+    let name = normalize_collection(name)?;
+    store
+        .with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let now = now_ts();
+            tx.execute(
+                "INSERT OR IGNORE INTO collection (name, description, created_ts) VALUES (?1, '', ?2)",
+                params![name, now],
+            )?;
+            let collection: i64 = tx.query_row("SELECT id FROM collection WHERE name = ?1", [&name], |row| row.get(0))?;
+            let mut added = 0;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO collection_item (collection_id, item_id, added_ts)
+                     SELECT ?1, id, ?3 FROM item WHERE id = ?2",
+                )?;
+                for id in ids {
+                    added += stmt.execute(params![collection, id, now])?;
+                }
+            }
+            tx.commit()?;
+            Ok(added)
+        })
+        .map_err(|e| format!("Failed to add to collection: {}", e))
+}
+
+/// Apply the auto-tag rules to a freshly captured item.
+/// Screenshots only have their OCR text later, so only source rules catch them here
+pub fn auto_tag(app: &AppHandle, item: &Item) {
+    let Some(tagger) = app.try_state::<Tagger>() else {
+        return;
+    };
+    let tags = tagger.tags_for(item);
+    if tags.is_empty() {
+        return;
+    }
+    if let Err(e) = add(&app.state::<Store>(), &[item.id], &tags) {
+        eprintln!("[TAGS] Failed to auto-tag item {}: {}", item.id, e);
+    }
+}
+
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    app.state::<Store>()
+        .with_conn(|conn| {
+            conn.execute_batch(SCHEMA)?;
+            // Items deleted by the Python side
+            conn.execute("DELETE FROM item_tag WHERE item_id NOT IN (SELECT id FROM item)", [])?;
+            conn.execute(
+                "DELETE FROM collection_item WHERE item_id NOT IN (SELECT id FROM item)",
+                [],
+            )?;
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to create tag tables: {}", e))?;
+
+    app.manage(Tagger::new(&settings::get(app).tags));
+    Ok(())
+}
+
+/// Every tag with how many items have it, most used first
+#[tauri::command]
+pub async fn list_tags(store: State<'_, Store>) -> Result<Vec<TagInfo>, String> {
+    store
+        .with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT t.name, COUNT(item.id) FROM tag t
+                 LEFT JOIN item_tag it ON it.tag_id = t.id
+                 LEFT JOIN item ON item.id = it.item_id
+                 GROUP BY t.id ORDER BY COUNT(item.id) DESC, t.name",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(TagInfo {
+                    name: row.get(0)?,
+                    items: row.get(1)?,
+                })
+            })?;
+            rows.collect()
+        })
+        .map_err(|e| format!("Failed to list tags: {}", e))
+}
+
+#[tauri::command]
+pub async fn item_tags(store: State<'_, Store>, id: i64) -> Result<Vec<String>, String> {
+    for_item(&store, id).map_err(|e| format!("Failed to load tags: {}", e))
+}
+
+/// Add `tags` to every item in `ids`, creating tags that don't exist yet.
+/// Returns how many tags were newly added
+#[tauri::command]
+pub async fn tag_items(store: State<'_, Store>, ids: Vec<i64>, tags: Vec<String>) -> Result<usize, String> {
+    let tags = normalize_tags(&tags)?;
+    add(&store, &ids, &tags).map_err(|e| format!("Failed to tag items: {}", e))
+}
+
+#[tauri::command]
+pub async fn untag_items(store: State<'_, Store>, ids: Vec<i64>, tags: Vec<String>) -> Result<usize, String> {
+    let tags = normalize_tags(&tags)?;
+    remove(&store, &ids, &tags).map_err(|e| format!("Failed to untag items: {}", e))
+}
+
+/// Rename a tag, merging it into `to` when that tag already exists
+#[tauri::command]
+pub async fn rename_tag(store: State<'_, Store>, from: String, to: String) -> Result<(), String> {
+    let (from, to) = (normalize_tag(&from)?, normalize_tag(&to)?);
+    if from == to {
+        return Ok(());
+    }
+
+    store
+        .with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let Some(from_id) = tag_id(&tx, &from)? else {
+                return Ok(Err(format!("Tag {} not found", from)));
+            };
+            let to_id = ensure_tag(&tx, &to)?;
+            tx.execute(
+                "INSERT OR IGNORE INTO item_tag (item_id, tag_id) SELECT item_id, ?2 FROM item_tag WHERE tag_id = ?1",
+                params![from_id, to_id],
+            )?;
+            tx.execute("DELETE FROM item_tag WHERE tag_id = ?1", [from_id])?;
+            tx.execute("DELETE FROM tag WHERE id = ?1", [from_id])?;
+            tx.commit()?;
+            Ok(Ok(()))
+        })
+        .map_err(|e| format!("Failed to rename tag: {}", e))?
+}
+
+/// Remove a tag from every item, the items themselves stay
+#[tauri::command]
+pub async fn delete_tag(store: State<'_, Store>, name: String) -> Result<(), String> {
+    let name = normalize_tag(&name)?;
+    let deleted = store
+        .with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                "DELETE FROM item_tag WHERE tag_id IN (SELECT id FROM tag WHERE name = ?1)",
+                [&name],
+            )?;
+            let deleted = tx.execute("DELETE FROM tag WHERE name = ?1", [&name])?;
+            tx.commit()?;
+            Ok(deleted > 0)
+        })
+        .map_err(|e| format!("Failed to delete tag: {}", e))?;
+
+    if !deleted {
+        return Err(format!("Tag {} not found", name));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_collections(store: State<'_, Store>) -> Result<Vec<Collection>, String> {
+    store
+        .with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT c.id, c.name, c.description, COUNT(item.id), c.created_ts FROM collection c
+                 LEFT JOIN collection_item ci ON ci.collection_id = c.id
+                 LEFT JOIN item ON item.id = ci.item_id
+                 GROUP BY c.id ORDER BY c.name",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(Collection {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    items: row.get(3)?,
+                    created_ts: row.get(4)?,
+                })
+            })?;
+            rows.collect()
+        })
+        .map_err(|e| format!("Failed to list collections: {}", e))
+}
+
+/// Names are unique regardless of case
+#[tauri::command]
+pub async fn create_collection(
+    store: State<'_, Store>,
+    name: String,
+    description: Option<String>,
+) -> Result<Collection, String> {
+    let name = normalize_collection(&name)?;
+    let description = description.unwrap_or_default();
+    let created_ts = now_ts();
+
+    let id = store
+        .with_conn(|conn| {
+            if collection_id(conn, &name)?.is_some() {
+                return Ok(None);
+            }
+            conn.execute(
+                "INSERT INTO collection (name, description, created_ts) VALUES (?1, ?2, ?3)",
+                params![name, description, created_ts],
+            )?;
+            Ok(Some(conn.last_insert_rowid()))
+        })
+        .map_err(|e| format!("Failed to create collection: {}", e))?
+        .ok_or_else(|| format!("Collection {} already exists", name))?;
+
+    Ok(Collection {
+        id,
+        name,
+        description,
+        items: 0,
+        created_ts,
+    })
+}
+
+/// Delete a collection, its items stay in history
+#[tauri::command]
+pub async fn delete_collection(store: State<'_, Store>, name: String) -> Result<(), String> {
+    let deleted = store
+        .with_conn(|conn| {
+            let Some(id) = collection_id(conn, name.trim())? else {
+                return Ok(false);
+            };
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM collection_item WHERE collection_id = ?1", [id])?;
+            tx.execute("DELETE FROM collection WHERE id = ?1", [id])?;
+            tx.commit()?;
+            Ok(true)
+        })
+        .map_err(|e| format!("Failed to delete collection: {}", e))?;
+
+    if !deleted {
+        return Err(format!("Collection {} not found", name.trim()));
+    }
+    Ok(())
+}
+
+/// Returns how many items were newly added
+#[tauri::command]
+pub async fn add_to_collection(store: State<'_, Store>, name: String, ids: Vec<i64>) -> Result<usize, String> {
+    store
+        .with_conn(|conn| {
+            let Some(collection) = collection_id(conn, name.trim())? else {
+                return Ok(Err(format!("Collection {} not found", name.trim())));
+            };
+            let tx = conn.unchecked_transaction()?;
+            let mut added = 0;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO collection_item (collection_id, item_id, added_ts)
+                     SELECT ?1, id, ?3 FROM item WHERE id = ?2",
+                )?;
+                let now = now_ts();
+                for id in &ids {
+                    added += stmt.execute(params![collection, id, now])?;
+                }
+            }
+            tx.commit()?;
+            Ok(Ok(added))
+        })
+        .map_err(|e| format!("Failed to add to collection: {}", e))?
+}
+
+/// Returns how many items were taken out
+#[tauri::command]
+pub async fn remove_from_collection(store: State<'_, Store>, name: String, ids: Vec<i64>) -> Result<usize, String> {
+    store
+        .with_conn(|conn| {
+            let Some(collection) = collection_id(conn, name.trim())? else {
+                return Ok(Err(format!("Collection {} not found", name.trim())));
+            };
+            let mut stmt =
+                conn.prepare_cached("DELETE FROM collection_item WHERE collection_id = ?1 AND item_id = ?2")?;
+            let mut removed = 0;
+            for id in &ids {
+                removed += stmt.execute(params![collection, id])?;
+            }
+            Ok(Ok(removed))
+        })
+        .map_err(|e| format!("Failed to remove from collection: {}", e))?
+}
+
+/// Items in a collection, most recently added first
+#[tauri::command]
+pub async fn collection_items(store: State<'_, Store>, name: String) -> Result<Vec<Item>, String> {
+    store
+        .with_conn(|conn| {
+            let sql = format!(
+                "SELECT {} FROM item JOIN (
+                     SELECT ci.item_id, ci.added_ts FROM collection_item ci
+                     JOIN collection c ON c.id = ci.collection_id
+                     WHERE c.name = ?1
+                 ) m ON m.item_id = item.id
+                 ORDER BY m.added_ts DESC, item.id DESC",
+                ITEM_COLUMNS
+            );
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let rows = stmt.query_map([name.trim()], Item::from_row)?;
+            rows.collect()
+        })
+        .map_err(|e| format!("Failed to list collection: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> Filter {
+        Filter::Tag(name.to_string())
+    }
+
+    fn collection(name: &str) -> Filter {
+        Filter::Collection(name.to_string())
+    }
+
+    #[test]
+    fn normalize_tag_rules() {
+        assert_eq!(normalize_tag("  #Work ").unwrap(), "work");
+        assert_eq!(normalize_tag("Q3/Reports_v2.1").unwrap(), "q3/reports_v2.1");
+        assert_eq!(normalize_tag("Ärger").unwrap(), "ärger");
+        assert!(normalize_tag("#").is_err());
+        assert!(normalize_tag("two words").is_err());
+        assert!(normalize_tag("a,b").is_err());
+        assert!(normalize_tag(&"x".repeat(MAX_TAG_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn query_without_filters() {
+        assert_eq!(parse_query("  quarterly   report "), ("quarterly report".to_string(), Vec::new()));
+        // A bare prefix is just a word
+        assert_eq!(parse_query("tag: notes"), ("tag: notes".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn prefixes_are_case_insensitive() {
+        assert_eq!(parse_query("TAG:Work Collection:Inbox"), (String::new(), vec![tag("work"), collection("Inbox")]));
+    }
+
+    #[test]
+    fn tag_filters_are_normalized_like_tags() {
+        assert_eq!(parse_query("tag:#work").1, [tag("work")]);
+        assert_eq!(parse_query("tag:\"#Work \"").1, [tag("work")]);
+    }
+
+    #[test]
+    fn quoted_values() {
+        assert_eq!(
+            parse_query("tag:\"two words\" collection:\"Trip to Rome\" tickets"),
+            ("tickets".to_string(), vec![tag("two words"), collection("Trip to Rome")])
+        );
+        // Unclosed quote runs to the end
+        assert_eq!(parse_query("collection:\"My stuff").1, [collection("My stuff")]);
+    }
+
+    #[test]
+    fn filters_mixed_with_text() {
+        assert_eq!(
+            parse_query("invoice tag:finance march collection:Taxes 2024"),
+            ("invoice march 2024".to_string(), vec![tag("finance"), collection("Taxes")])
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use image::imageops::FilterType;
+use image::ImageFormat;
+use tauri::ipc::Response;
+use tauri::Manager;
+use xxhash_rust::xxh64::xxh64;
+
+use crate::image_ipc::{self, PixelFormat};
+use crate::image_sandbox::{ImageError, ImageSandbox};
+
+// Thumbnails are for previews, anything bigger should use read_image_file or stream_image_file
+const MAX_THUMBNAIL_SIDE: u32 = 1024;
+
+/// Folder inside the app cache dir where encoded thumbnails are kept
+pub fn cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to resolve cache dir: {}", e))?;
+    Ok(dir.join("thumbnails"))
+}
+
+fn path_key(path: &Path) -> String {
+    format!("{:016x}-", xxh64(path.to_string_lossy().as_bytes(), 0))
+}
+
+/// Cache file name for a thumbnail. Keyed by path + mtime so an edited
+/// screenshot gets a fresh preview instead of the stale one.
+/// Starts with a hash of the path alone so `remove_cached` can find every size
+fn cache_file_name(path: &Path, modified: u64, max_w: u32, max_h: u32) -> String {
+    let key = format!("{}|{}|{}x{}", path.display(), modified, max_w, max_h);
+    format!("{}{:016x}.png", path_key(path), xxh64(key.as_bytes(), 0))
+}
+
+/// Delete every cached thumbnail of `path`, returns how many files went
+pub fn remove_cached(cache_dir: &Path, path: &Path) -> usize {
+    let prefix = path_key(path);
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+/// Downscale `path` to fit inside max_w x max_h and return it as PNG bytes,
+/// reusing a cached copy from `cache_dir` when the source hasn't changed.
+/// The path goes through the sandbox even on a cache hit
+pub fn thumbnail_png(
+    sandbox: &ImageSandbox,
+    cache_dir: &Path,
+    path: &str,
+    max_w: u32,
+    max_h: u32,
+) -> Result<Vec<u8>, ImageError> {
+    let max_w = max_w.clamp(1, MAX_THUMBNAIL_SIDE);
+    let max_h = max_h.clamp(1, MAX_THUMBNAIL_SIDE);
+
+    let path = sandbox.check(path)?;
+    let modified = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .map_err(|e| ImageError::Io(e.to_string()))?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Sealed when the history is encrypted, and skipped altogether while it's locked
+    let blobs = sandbox.blobs();
+    let cached = cache_dir.join(cache_file_name(&path, modified, max_w, max_h));
+    if let Ok(bytes) = blobs.read(&cached) {
+        return Ok(bytes);
+    }
+
+    let img = sandbox.decode(&path)?;
+
+    // Keeps aspect ratio, and never upscale images that already fit
+    let thumb = if img.width() > max_w || img.height() > max_h {
+        img.resize(max_w, max_h, FilterType::Lanczos3)
+    } else {
+        img
+    };
+
+    let mut out = Cursor::new(Vec::new());
+    thumb
+        .write_to(&mut out, ImageFormat::Png)
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    let bytes = out.into_inner();
+
+    // A failed cache write only means we regenerate next time.
+    // Write to a temp file first so a concurrent read never sees half a PNG
+    if fs::create_dir_all(cache_dir).is_ok() {
+        let tmp = cached.with_extension("tmp");
+        if blobs.write(&tmp, &bytes).is_ok() {
+            let _ = fs::rename(&tmp, &cached);
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[tauri::command]
+pub async fn read_image_thumbnail(
+    app: tauri::AppHandle,
+    path: String,
+    max_w: u32,
+    max_h: u32,
+) -> Result<Response, ImageError> {
+    let cache_dir = cache_dir(&app).map_err(ImageError::Io)?;
+
+    // Decoding a full screenshot takes a while, keep it off the IPC thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let sandbox = app.state::<ImageSandbox>();
+        let png = thumbnail_png(&sandbox, &cache_dir, &path, max_w, max_h)?;
+        let (width, height) = image_ipc::dimensions(&png)?;
+        Ok(Response::new(image_ipc::frame(PixelFormat::Png, width, height, &png)))
+    })
+    .await
+    .map_err(|e| ImageError::Io(format!("Thumbnail task failed: {}", e)))?
+}
@@ -0,0 +1,207 @@
+// Tray icon with the latest captures and capture controls
+// Keeps ClipMind (and its hotkeys) alive when the dashboard is closed
+
+use std::thread;
+use std::time::Duration;
+
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager};
+
+use crate::capture::{self, CaptureState};
+use crate::storage::{Item, Store};
+use crate::{quickboard, representations, vault};
+
+const TRAY_ID: &str = "clipmind";
+const MAIN_LABEL: &str = "main";
+
+const RECENT_COUNT: u32 = 10;
+const LABEL_CHARS: usize = 40;
+
+// Picks up items the Python watchers insert, there's no event for those
+const REFRESH_MS: u64 = 3000;
+
+const ITEM_PREFIX: &str = "item:";
+const PAUSE_ID: &str = "pause";
+const PAUSE_FOR_PREFIX: &str = "pause-for:";
+const QUICKBOARD_ID: &str = "quickboard";
+const DASHBOARD_ID: &str = "dashboard";
+const QUIT_ID: &str = "quit";
+
+pub fn start(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app, &recent(app))?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("ClipMind")
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let handle = app.clone();
+    thread::Builder::new()
+        .name("tray-refresh".into())
+        .spawn(move || {
+            let mut shown: Vec<i64> = Vec::new();
+            loop {
+                let items = recent(&handle);
+                let ids: Vec<i64> = items.iter().map(|item| item.id).collect();
+                if ids != shown {
+                    shown = ids;
+                    set_menu(&handle, &items);
+                }
+                thread::sleep(Duration::from_millis(REFRESH_MS));
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Rebuild the menu, e.g. after the capture state changed
+pub fn refresh(app: &AppHandle) {
+    set_menu(app, &recent(app));
+}
+
+fn set_menu(app: &AppHandle, items: &[Item]) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let state = app.state::<capture::Capture>().state();
+    let tooltip = match state {
+        CaptureState::Recording => "ClipMind".to_string(),
+        paused => format!("ClipMind - {}", capture::describe(paused)),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+
+    match build_menu(app, items) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                eprintln!("[TRAY] Failed to set menu: {}", e);
+            }
+        }
+        Err(e) => eprintln!("[TRAY] Failed to build menu: {}", e),
+    }
+}
+
+fn recent(app: &AppHandle) -> Vec<Item> {
+    if vault::is_locked(app) {
+        return Vec::new();
+    }
+    app.state::<Store>()
+        .recent(RECENT_COUNT, None, None)
+        .unwrap_or_else(|e| {
+            eprintln!("[TRAY] Failed to load recent items: {}", e);
+            Vec::new()
+        })
+}
+
+/// One line, short enough for a menu
+fn item_label(item: &Item) -> String {
+    let text = item.text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = match text.char_indices().nth(LABEL_CHARS) {
+        Some((cut, _)) => format!("{}...", &text[..cut]),
+        None => text,
+    };
+
+    let label = if item.source == "screenshot" {
+        format!("[img] {}", text)
+    } else {
+        text
+    };
+    // A lone & would be eaten as a mnemonic marker
+    label.replace('&', "&&")
+}
+
+fn build_menu(app: &AppHandle, items: &[Item]) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+
+    if items.is_empty() {
+        menu.append(&MenuItem::new(app, "No items yet", false, None::<&str>)?)?;
+    }
+    for item in items {
+        let id = format!("{}{}", ITEM_PREFIX, item.id);
+        menu.append(&MenuItem::with_id(app, id, item_label(item), true, None::<&str>)?)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    let state = app.state::<capture::Capture>().state();
+    let pause_label = match state {
+        CaptureState::Recording => "Pause capture".to_string(),
+        paused => capture::describe(paused),
+    };
+    menu.append(&CheckMenuItem::with_id(
+        app,
+        PAUSE_ID,
+        pause_label,
+        true,
+        state != CaptureState::Recording,
+        None::<&str>,
+    )?)?;
+    if state == CaptureState::Recording {
+        for (secs, label) in [(15 * 60, "Pause for 15 minutes"), (3600, "Pause for 1 hour")] {
+            let id = format!("{}{}", PAUSE_FOR_PREFIX, secs);
+            menu.append(&MenuItem::with_id(app, id, label, true, None::<&str>)?)?;
+        }
+    }
+    menu.append(&MenuItem::with_id(app, QUICKBOARD_ID, "Open Quickboard", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, DASHBOARD_ID, "Show Dashboard", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, QUIT_ID, "Quit ClipMind", true, None::<&str>)?)?;
+
+    Ok(menu)
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        PAUSE_ID => {
+            if capture::is_paused(app) {
+                capture::resume(app);
+            } else {
+                capture::pause(app, None);
+            }
+        }
+        QUICKBOARD_ID => quickboard::show(app),
+        DASHBOARD_ID => show_main(app),
+        // Goes through RunEvent::Exit, so the backend is still shut down
+        QUIT_ID => app.exit(0),
+        id if id.starts_with(PAUSE_FOR_PREFIX) => {
+            let secs = id[PAUSE_FOR_PREFIX.len()..].parse().ok();
+            capture::pause(app, secs);
+        }
+        id => {
+            let Some(item_id) = id.strip_prefix(ITEM_PREFIX).and_then(|s| s.parse().ok()) else {
+                return;
+            };
+
+            // Decoding a screenshot can take a moment, don't block the menu
+            let app = app.clone();
+            thread::spawn(move || {
+                if let Err(e) = representations::copy(&app, item_id, None) {
+                    eprintln!("[TRAY] {}", e);
+                }
+            });
+        }
+    }
+}
+
+pub fn show_main(app: &AppHandle) {
+    if let Some(main) = app.get_webview_window(MAIN_LABEL) {
+        let _ = main.show();
+        let _ = main.unminimize();
+        let _ = main.set_focus();
+    }
+}
+
+/// Closing the dashboard only hides it, quitting goes through the tray
+pub fn on_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
+    if window.label() != MAIN_LABEL {
+        return;
+    }
+    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        api.prevent_close();
+        let _ = window.hide();
+    }
+}
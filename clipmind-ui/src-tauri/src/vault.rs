@@ -0,0 +1,528 @@
+// Encryption at rest for the history. Off until `encrypt_history` is run once,
+// after that clipmind.db is a SQLCipher database and files ClipMind writes itself
+// (the blob store, cached thumbnails) are sealed with ChaCha20-Poly1305.
+// Both keys come from the passphrase through Argon2id and only live in memory:
+// the app starts locked, `unlock` opens the history, `lock` or the idle timer
+// ([vault] auto_lock_mins) drops them. Nothing is captured while locked.
+// The salt and KDF params sit next to the db in clipmind.db.vault, none of it is secret
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rusqlite::{Connection, ErrorCode};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, Window, WindowEvent};
+use zeroize::Zeroizing;
+
+use crate::backend::Backend;
+use crate::image_sandbox::ImageSandbox;
+use crate::search::SearchIndex;
+use crate::storage::{self, Store};
+use crate::{settings, thumbnail, tray};
+
+pub const LOCKED_EVENT: &str = "vault://locked";
+pub const UNLOCKED_EVENT: &str = "vault://unlocked";
+
+const HEADER_VERSION: u32 = 1;
+
+// OWASP's Argon2id baseline, about a second on a laptop
+const M_COST_KIB: u32 = 64 * 1024;
+const T_COST: u32 = 3;
+const P_COST: u32 = 1;
+const SALT_LEN: usize = 16;
+
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+// Start of every sealed file, then the nonce, then ciphertext + tag
+const MAGIC: &[u8] = b"CMVAULT1";
+const NONCE_LEN: usize = 12;
+
+const IDLE_CHECK_SECS: u64 = 30;
+
+// Python can still have the db open for a moment after it's killed (Windows won't rename then)
+const RENAME_ATTEMPTS: u32 = 20;
+const RENAME_RETRY_MS: u64 = 250;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultSettings {
+    /// Lock after this long without using ClipMind, 0 never locks on its own
+    pub auto_lock_mins: u32,
+}
+
+impl Default for VaultSettings {
+    fn default() -> Self {
+        VaultSettings { auto_lock_mins: 15 }
+    }
+}
+
+/// clipmind.db.vault, written once when the history gets encrypted
+#[derive(Clone, Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    kdf: String,
+    /// Hex
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl Header {
+    fn new() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Header {
+            version: HEADER_VERSION,
+            kdf: "argon2id".to_string(),
+            salt: hex(&salt).to_string(),
+            m_cost: M_COST_KIB,
+            t_cost: T_COST,
+            p_cost: P_COST,
+        }
+    }
+}
+
+struct Keys {
+    /// Raw SQLCipher key
+    db: Zeroizing<[u8; 32]>,
+    blobs: ChaCha20Poly1305,
+}
+
+/// How files ClipMind owns are read and written
+#[derive(Clone)]
+pub enum Blobs {
+    Plain,
+    Sealed(ChaCha20Poly1305),
+    Locked,
+}
+
+impl Blobs {
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self {
+            Blobs::Plain => fs::read(path),
+            Blobs::Sealed(cipher) => open(cipher, &fs::read(path)?),
+            Blobs::Locked => Err(locked_io()),
+        }
+    }
+
+    pub fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Blobs::Plain => fs::write(path, bytes),
+            Blobs::Sealed(cipher) => fs::write(path, seal(cipher, bytes)?),
+            Blobs::Locked => Err(locked_io()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct VaultStatus {
+    pub encrypted: bool,
+    pub locked: bool,
+    pub auto_lock_mins: u32,
+}
+
+pub struct Vault {
+    header_path: PathBuf,
+    header: Mutex<Option<Header>>,
+    keys: Mutex<Option<Keys>>,
+    last_active: Mutex<Instant>,
+    // start_history only runs once, on the first unlock when the app started locked
+    history_started: Mutex<bool>,
+}
+
+impl Vault {
+    fn keys(&self) -> std::sync::MutexGuard<'_, Option<Keys>> {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.header.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.is_encrypted() && self.keys().is_none()
+    }
+
+    pub fn blobs(&self) -> Blobs {
+        if !self.is_encrypted() {
+            return Blobs::Plain;
+        }
+        match self.keys().as_ref() {
+            Some(keys) => Blobs::Sealed(keys.blobs.clone()),
+            None => Blobs::Locked,
+        }
+    }
+
+    /// Hex SQLCipher key for the Python side, None when the history isn't encrypted
+    pub fn db_key_hex(&self) -> Option<Zeroizing<String>> {
+        self.keys().as_ref().map(|keys| hex(&keys.db[..]))
+    }
+
+    /// Push back the idle timer
+    pub fn touch(&self) {
+        *self.last_active.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_active.lock().unwrap_or_else(|e| e.into_inner()).elapsed()
+    }
+}
+
+fn hex(bytes: &[u8]) -> Zeroizing<String> {
+    Zeroizing::new(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn locked_io() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "History is locked")
+}
+
+fn seal(cipher: &ChaCha20Poly1305, plain: &[u8]) -> io::Result<Vec<u8>> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|_| io::Error::other("Failed to encrypt blob"))?;
+    Ok([MAGIC, &nonce[..], &sealed].concat())
+}
+
+fn open(cipher: &ChaCha20Poly1305, data: &[u8]) -> io::Result<Vec<u8>> {
+    let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "Not a sealed blob");
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|body| body.len() >= NONCE_LEN)
+        .ok_or_else(corrupt)?;
+    let (nonce, sealed) = body.split_at(NONCE_LEN);
+    let nonce = Nonce::from(<[u8; NONCE_LEN]>::try_from(nonce).map_err(|_| corrupt())?);
+    cipher
+        .decrypt(&nonce, sealed)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Blob failed authentication"))
+}
+
+/// Argon2id over the passphrase, split into the db key and the blob key
+fn derive(header: &Header, passphrase: &str) -> Result<Keys, String> {
+    let salt = unhex(&header.salt).ok_or("Vault header has a malformed salt")?;
+    let params = Params::new(header.m_cost, header.t_cost, header.p_cost, Some(64))
+        .map_err(|e| format!("Vault header has bad KDF params: {}", e))?;
+
+    let mut out = Zeroizing::new([0u8; 64]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut out[..])
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+
+    let mut db = Zeroizing::new([0u8; 32]);
+    db.copy_from_slice(&out[..32]);
+    Ok(Keys {
+        db,
+        blobs: ChaCha20Poly1305::new(&Key::from(
+            <[u8; 32]>::try_from(&out[32..]).map_err(|_| "Derived key has the wrong length")?,
+        )),
+    })
+}
+
+fn header_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(".vault");
+    PathBuf::from(name)
+}
+
+fn read_header(path: &Path) -> Result<Option<Header>, String> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let header: Header = serde_json::from_str(&raw).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    if header.version > HEADER_VERSION || header.kdf != "argon2id" {
+        return Err(format!("{} is from a newer version of ClipMind", path.display()));
+    }
+    Ok(Some(header))
+}
+
+fn write_header(path: &Path, header: &Header) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(header).map_err(|e| format!("Failed to serialize vault header: {}", e))?;
+    let tmp = path.with_extension("vault.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Is the history locked. False when it isn't encrypted or before `start`
+pub fn is_locked(app: &AppHandle) -> bool {
+    app.try_state::<Vault>().is_some_and(|vault| vault.is_locked())
+}
+
+pub fn blobs(app: &AppHandle) -> Blobs {
+    app.try_state::<Vault>().map(|vault| vault.blobs()).unwrap_or(Blobs::Plain)
+}
+
+pub fn db_key_hex(app: &AppHandle) -> Option<Zeroizing<String>> {
+    app.try_state::<Vault>().and_then(|vault| vault.db_key_hex())
+}
+
+/// Focusing a window counts as using ClipMind
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::Focused(true) = event {
+        if let Some(vault) = window.try_state::<Vault>() {
+            vault.touch();
+        }
+    }
+}
+
+fn set_blobs(app: &AppHandle, blobs: Blobs) {
+    if let Some(sandbox) = app.try_state::<ImageSandbox>() {
+        sandbox.set_blobs(blobs);
+    }
+}
+
+/// Take `keys` into use: open the db with them and start whatever waited for it
+fn open_with(app: &AppHandle, vault: &Vault, keys: Keys) -> Result<(), String> {
+    app.state::<Store>().reopen(Some(&keys.db[..])).map_err(|e| match e.sqlite_error_code() {
+        Some(ErrorCode::NotADatabase) => "Wrong passphrase".to_string(),
+        _ => format!("Failed to open history: {}", e),
+    })?;
+
+    let blobs = Blobs::Sealed(keys.blobs.clone());
+    *vault.keys() = Some(keys);
+    vault.touch();
+    set_blobs(app, blobs);
+
+    let first = !std::mem::replace(&mut *vault.history_started.lock().unwrap_or_else(|e| e.into_inner()), true);
+    if first {
+        crate::start_history(app)?;
+    }
+
+    tray::refresh(app);
+    if let Err(e) = app.emit(UNLOCKED_EVENT, ()) {
+        eprintln!("[VAULT] Failed to emit unlock: {}", e);
+    }
+    Ok(())
+}
+
+/// Drop the keys and everything decrypted that's still in memory
+fn lock(app: &AppHandle) {
+    let vault = app.state::<Vault>();
+    if vault.keys().take().is_none() {
+        return;
+    }
+
+    app.state::<Store>().close();
+    app.state::<SearchIndex>().clear();
+    set_blobs(app, Blobs::Locked);
+    // They have the key too, the supervisor waits for the next unlock to start them again
+    if let Some(backend) = app.try_state::<Backend>() {
+        backend.restart_all();
+    }
+
+    tray::refresh(app);
+    if let Err(e) = app.emit(LOCKED_EVENT, ()) {
+        eprintln!("[VAULT] Failed to emit lock: {}", e);
+    }
+}
+
+/// Copy the plaintext db at `plain` into a SQLCipher db at `target`
+fn export_encrypted(plain: &Path, target: &Path, key: &[u8]) -> rusqlite::Result<()> {
+    let _ = fs::remove_file(target);
+    let conn = Connection::open(plain)?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    // Fold any WAL back in so the export sees everything
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    let attach = Zeroizing::new(format!(
+        "ATTACH DATABASE ?1 AS encrypted KEY \"x'{}'\"",
+        hex(key).as_str()
+    ));
+    conn.execute(&attach, [target.to_string_lossy()])?;
+    conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+    conn.execute("DETACH DATABASE encrypted", [])?;
+    Ok(())
+}
+
+fn rename_retrying(from: &Path, to: &Path) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match fs::rename(from, to) {
+            Err(_) if attempt < RENAME_ATTEMPTS => {
+                attempt += 1;
+                thread::sleep(Duration::from_millis(RENAME_RETRY_MS));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Seal every file in the blob store that isn't sealed yet, returns how many
+fn seal_blobs(dir: &Path, cipher: &ChaCha20Poly1305) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(0);
+    };
+
+    let mut sealed = 0;
+    for path in entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()) {
+        let data = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if data.starts_with(MAGIC) {
+            continue;
+        }
+        let tmp = path.with_extension("sealing");
+        fs::write(&tmp, seal(cipher, &data).map_err(|e| e.to_string())?)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to seal {}: {}", path.display(), e))?;
+        sealed += 1;
+    }
+    Ok(sealed)
+}
+
+/// Encrypt the plaintext history in place with a key from `passphrase`
+fn encrypt(app: &AppHandle, passphrase: &str) -> Result<(), String> {
+    let vault = app.state::<Vault>();
+    if vault.is_encrypted() {
+        return Err("History is already encrypted".to_string());
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!("Passphrase needs at least {} characters", MIN_PASSPHRASE_CHARS));
+    }
+
+    let header = Header::new();
+    let keys = derive(&header, passphrase)?;
+
+    // From here on the history counts as locked: capture stops, the backend
+    // waits for the key, and nothing else has the file open
+    *vault.header.lock().unwrap_or_else(|e| e.into_inner()) = Some(header.clone());
+    let store = app.state::<Store>();
+    store.close();
+    if let Some(backend) = app.try_state::<Backend>() {
+        backend.restart_all();
+    }
+
+    let db_path = store.path().to_path_buf();
+    let target = db_path.with_extension("db.encrypting");
+    let rollback = |e: String| {
+        let _ = fs::remove_file(&target);
+        *vault.header.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        if let Err(e) = store.reopen(None) {
+            eprintln!("[VAULT] Failed to reopen history: {}", e);
+        }
+        e
+    };
+
+    export_encrypted(&db_path, &target, &keys.db[..]).map_err(|e| rollback(format!("Failed to encrypt history: {}", e)))?;
+    // Header first: a db nobody has the salt for can never be opened again
+    write_header(&vault.header_path, &header).map_err(rollback)?;
+    if let Err(e) = rename_retrying(&target, &db_path) {
+        let _ = fs::remove_file(&vault.header_path);
+        return Err(rollback(format!("Failed to replace {}: {}", db_path.display(), e)));
+    }
+
+    if let Ok(dir) = storage::blob_dir(app) {
+        let sealed = seal_blobs(&dir, &keys.blobs)?;
+        eprintln!("[VAULT] Sealed {} blobs", sealed);
+    }
+    // Thumbnails were written in the clear, they come back sealed on demand
+    if let Ok(dir) = thumbnail::cache_dir(app) {
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    open_with(app, &vault, keys)
+}
+
+/// Manage the Vault and the Store, opened right away unless the history is encrypted
+pub fn start(app: &AppHandle, db_path: &Path) -> Result<(), String> {
+    let header_path = header_path(db_path);
+    let header = read_header(&header_path)?;
+
+    let store = match header {
+        Some(_) => Store::locked(db_path),
+        None => Store::open(db_path).map_err(|e| format!("Failed to open {}: {}", db_path.display(), e))?,
+    };
+    app.manage(store);
+    app.manage(Vault {
+        header_path,
+        // A plaintext history is started by setup right away
+        history_started: Mutex::new(header.is_none()),
+        header: Mutex::new(header),
+        keys: Mutex::new(None),
+        last_active: Mutex::new(Instant::now()),
+    });
+
+    let app = app.clone();
+    thread::Builder::new()
+        .name("vault-idle".into())
+        .spawn(move || loop {
+            thread::sleep(Duration::from_secs(IDLE_CHECK_SECS));
+            let mins = settings::get(&app).vault.auto_lock_mins;
+            let vault = app.state::<Vault>();
+            if mins > 0 && vault.is_encrypted() && !vault.is_locked() && vault.idle_for() >= Duration::from_secs(mins as u64 * 60) {
+                eprintln!("[VAULT] Idle for {} minutes, locking", mins);
+                lock(&app);
+            }
+        })
+        .map_err(|e| format!("Failed to start idle lock: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn vault_status(app: AppHandle, vault: State<'_, Vault>) -> VaultStatus {
+    VaultStatus {
+        encrypted: vault.is_encrypted(),
+        locked: vault.is_locked(),
+        auto_lock_mins: settings::get(&app).vault.auto_lock_mins,
+    }
+}
+
+/// Open an encrypted history. Does nothing when it's already open
+#[tauri::command]
+pub async fn unlock(app: AppHandle, passphrase: String) -> Result<(), String> {
+    let passphrase = Zeroizing::new(passphrase);
+    tauri::async_runtime::spawn_blocking(move || {
+        let vault = app.state::<Vault>();
+        let Some(header) = vault.header.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+            return Ok(());
+        };
+        if !vault.is_locked() {
+            return Ok(());
+        }
+        let keys = derive(&header, &passphrase)?;
+        open_with(&app, &vault, keys)
+    })
+    .await
+    .map_err(|e| format!("Unlock failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn lock_history(app: AppHandle, vault: State<'_, Vault>) -> Result<(), String> {
+    if !vault.is_encrypted() {
+        return Err("History isn't encrypted, run encrypt_history first".to_string());
+    }
+    lock(&app);
+    Ok(())
+}
+
+/// The webview saw the user do something
+#[tauri::command]
+pub fn touch_vault(vault: State<'_, Vault>) {
+    vault.touch();
+}
+
+/// One-way: encrypt the existing plaintext history in place and unlock it
+#[tauri::command]
+pub async fn encrypt_history(app: AppHandle, passphrase: String) -> Result<(), String> {
+    let passphrase = Zeroizing::new(passphrase);
+    tauri::async_runtime::spawn_blocking(move || encrypt(&app, &passphrase))
+        .await
+        .map_err(|e| format!("Encryption failed: {}", e))?
+}
@@ -1,17 +1,87 @@
 import { useEffect, useMemo, useRef, useState } from "react";
 import "./App.css";
-import {
-  WebviewWindow,
-  getCurrentWebviewWindow,
-} from "@tauri-apps/api/webviewWindow";
-import {
-  register,
-  unregister,
-  isRegistered,
-} from "@tauri-apps/plugin-global-shortcut";
-import { writeText, writeImage } from "@tauri-apps/plugin-clipboard-manager";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { invoke } from "@tauri-apps/api/core";
-type HKStatus = "pending" | "ok" | "exists" | "failed";
+import { listen } from "@tauri-apps/api/event";
+
+interface HotkeyStatus {
+  action: string;
+  shortcut: string;
+  state: "registered" | "invalid" | "duplicate" | "unavailable";
+  error: string | null;
+}
+
+type CaptureState = { state: "recording" } | { state: "paused"; resume_at: number | null };
+
+interface AutoPasteStatus {
+  enabled: boolean;
+  delay_ms: number;
+  unavailable: string | null;
+}
+
+interface FieldError {
+  field: string;
+  message: string;
+}
+
+interface SettingsChanged {
+  settings: Record<string, unknown>;
+  changed: string[];
+  restart_required: string[];
+}
+
+interface VaultStatus {
+  encrypted: boolean;
+  locked: boolean;
+  auto_lock_mins: number;
+}
+
+interface ArchiveProgress {
+  operation: "export" | "import";
+  done: number;
+  total: number;
+}
+
+type ArchiveFormat = "json" | "ndjson" | "markdown" | "zip";
+
+const ARCHIVE_EXTENSIONS: Record<string, ArchiveFormat> = { json: "json", ndjson: "ndjson", jsonl: "ndjson", md: "markdown", zip: "zip" };
+
+// touch_vault at most this often, it only pushes back the auto-lock timer
+const TOUCH_INTERVAL_MS = 30_000;
+
+interface BackendProcess {
+  name: string;
+  running: boolean;
+  pid: number | null;
+  restarts: number;
+  last_exit_code: number | null;
+  last_error: string | null;
+  logs: { stream: string; line: string }[];
+}
+
+// Typed errors from read_image_file / read_image_thumbnail
+interface ImageError {
+  kind: string;
+  details: any;
+}
+
+function describeError(e: any): string {
+  if (e && typeof e === "object" && "kind" in e) {
+    const err = e as ImageError;
+    const details = typeof err.details === "string" ? err.details : JSON.stringify(err.details);
+    return `${err.kind}: ${details}`;
+  }
+  return e?.message ?? String(e);
+}
+
+interface ScanResult {
+  sensitive: boolean;
+  action: "allow" | "expire" | "redact" | "skip";
+  findings: { rule: string; action: string }[];
+}
+
+// scan_text results keyed by item id, items never change once stored
+const sensitiveCache = new Map<number, boolean>();
 
 interface ClipItem {
   id: number;
@@ -22,6 +92,14 @@ interface ClipItem {
   readable_time: string;
   score?: number;
   preview?: string;
+  // Set on items from list_pinned, 0 is the top
+  position?: number;
+}
+
+// copy_item result, the MIME types that ended up on the clipboard
+interface CopyResult {
+  id: number;
+  formats: string[];
 }
 
 function useCurrentLabel() {
@@ -39,80 +117,71 @@ function useCurrentLabel() {
   return label;
 }
 
-async function waitForCreated(win: WebviewWindow) {
-  return new Promise<void>((resolve, reject) => {
-    const offCreated = win.once("tauri://created", () => {
-      offCreated();
-      offError();
-      resolve();
-    });
-    const offError = win.once("tauri://error", (e) => {
-      offCreated();
-      offError();
-      reject(typeof e === "string" ? new Error(e) : (e as any));
-    });
-  });
+// Recent items come from the Rust item store, so they work without the backend.
+// Pinned items go on top, whatever the time range
+async function fetchRecent(filter: string, afterTimestamp: number | null): Promise<ClipItem[]> {
+  const source = filter === "clipboard" ? "clipboard" : filter === "images" ? "screenshot" : null;
+  const [pinned, recent] = await Promise.all([
+    invoke<ClipItem[]>("list_pinned", { source }),
+    invoke<ClipItem[]>("list_recent_items", {
+      limit: 20,
+      source,
+      after: afterTimestamp,
+    }),
+  ]);
+  const pinnedIds = new Set(pinned.map((item) => item.id));
+  return [...pinned, ...recent.filter((item) => !pinnedIds.has(item.id))];
 }
 
-async function ensureQuickboardWindow(log: (s: string) => void, pushErr: (s: string) => void) {
-  try {
-    let qb = WebviewWindow.getByLabel("quickboard");
-    if (qb) {
-      log("[qb] found existing quickboard window");
-      return qb;
-    }
+const isPinned = (item: ClipItem) => item.position !== undefined;
 
-    log("[qb] creating new quickboard webview...");
-    qb = new WebviewWindow("quickboard", {
-      url: "index.html",
-      visible: false,
-      decorations: false,
-      transparent: true,
-      alwaysOnTop: true,
-      resizable: false,
-      skipTaskbar: true,
-      focus: true,
-      width: 900,
-      height: 580,
-    });
+// tag:x and collection:x terms are handled by search_items in Rust
+const hasFilters = (query: string) => /(^|\s)(tag|collection):\S/i.test(query);
 
-    await waitForCreated(qb);
-    log("[qb] created successfully");
-    return qb;
-  } catch (e: any) {
-    const msg = `[qb] ensure failed: ${e?.message ?? String(e)}`;
-    log(msg);
-    pushErr(msg);
-    throw e;
-  }
+// Lower bound for the time range picker, null for all time
+function rangeStart(timeRange: string): number | null {
+  const now = Math.floor(Date.now() / 1000);
+  const spans: Record<string, number> = { hour: 3600, day: 86400, week: 604800, month: 2592000 };
+  return timeRange in spans ? now - spans[timeRange] : null;
 }
 
-let lastToggleTime = 0;
-const TOGGLE_COOLDOWN_MS = 200; // Reduced from 300ms
+// Item images are served by the clipmind:// protocol in Rust, no backend needed.
+// Windows webviews only accept custom schemes in the http://<scheme>.localhost form
+const ITEM_ORIGIN = navigator.userAgent.includes("Windows") ? "http://clipmind.localhost" : "clipmind://localhost";
 
-async function toggleQuickboard(log: (s: string) => void, pushErr: (s: string) => void) {
-  const now = Date.now();
-  if (now - lastToggleTime < TOGGLE_COOLDOWN_MS) {
-    log("[qb] toggle blocked (cooldown)");
-    return;
+function itemUrl(id: number, kind: "image" | "thumb" | "ocr", query = ""): string {
+  return `${ITEM_ORIGIN}/item/${id}/${kind}${query}`;
+}
+
+function Thumbnail({ id }: { id: number }) {
+  const [failed, setFailed] = useState(false);
+
+  if (failed) {
+    return <div style={{ width: "80px", height: "60px", borderRadius: "4px", background: "#2d2d2d", flexShrink: 0 }} />;
   }
-  lastToggleTime = now;
 
+  return (
+    <img
+      // 2x the displayed 80x60 so previews stay sharp on HiDPI screens
+      src={itemUrl(id, "thumb", "?w=160&h=120")}
+      alt="Screenshot preview"
+      onError={() => setFailed(true)}
+      style={{
+        width: "80px",
+        height: "60px",
+        objectFit: "cover",
+        borderRadius: "4px",
+        flexShrink: 0
+      }}
+    />
+  );
+}
+
+// Hotkeys and the quickboard window are owned by the Rust side
+async function toggleQuickboard(log: (s: string) => void, pushErr: (s: string) => void) {
   try {
-    const qb = await ensureQuickboardWindow(log, pushErr);
-    const visible = await qb.isVisible();
-
-    log(`[qb] current visible state: ${visible}`);
-
-    if (visible) {
-      await qb.hide();
-      log("[qb] hidden");
-    } else {
-      await qb.center();
-      await qb.show();
-      await qb.setFocus();
-      log("[qb] shown + focused + centered");
-    }
+    await invoke("toggle_quickboard");
+    log("[qb] toggled");
   } catch (e: any) {
     const msg = `[qb] toggle failed: ${e?.message ?? String(e)}`;
     log(msg);
@@ -132,18 +201,22 @@ export default function App() {
   const [backendStatus, setBackendStatus] = useState<"checking" | "online" | "offline">("checking");
   const [selectedIndex, setSelectedIndex] = useState(0);
 
-  const [hkStatuses, setHkStatuses] = useState<Record<string, HKStatus>>({});
+  const [hotkeys, setHotkeys] = useState<HotkeyStatus[]>([]);
   const [errors, setErrors] = useState<string[]>([]);
   const [logs, setLogs] = useState<string[]>([]);
   const [stats, setStats] = useState<any>(null);
+  const [processes, setProcesses] = useState<BackendProcess[]>([]);
+  const [capture, setCapture] = useState<CaptureState>({ state: "recording" });
+  const [autoPaste, setAutoPaste] = useState<AutoPasteStatus | null>(null);
+  const [vault, setVault] = useState<VaultStatus | null>(null);
+  const [archive, setArchive] = useState<ArchiveProgress | null>(null);
+  const lastTouch = useRef(0);
   const didAutoOpen = useRef(false);
   const itemRefs = useRef<(HTMLDivElement | null)[]>([]);
 
   const log = (s: string) => setLogs((L) => [...L, s]);
   const pushErr = (s: string) => setErrors((E) => [...E, s]);
 
-  const hotkeys = ["CommandOrControl+Shift+V"];
-
   // Auto-scroll selected item into view
   useEffect(() => {
     if (itemRefs.current[selectedIndex]) {
@@ -154,9 +227,155 @@ export default function App() {
     }
   }, [selectedIndex]);
 
+  // Capture state, changed from here, the tray or the resume timer
+  useEffect(() => {
+    invoke<CaptureState>("capture_state").then(setCapture).catch(() => {});
+    const unlisten = listen<CaptureState>("capture://state", (e) => setCapture(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  const setCapturePaused = (duration: number | null | false) => {
+    const call = duration === false ? invoke<CaptureState>("resume_capture") : invoke<CaptureState>("pause_capture", { duration });
+    call.then(setCapture).catch((e) => pushErr(`[capture] ${describeError(e)}`));
+  };
+
+  useEffect(() => {
+    invoke<AutoPasteStatus>("autopaste_settings").then(setAutoPaste).catch(() => {});
+  }, []);
+
+  // Encrypted history: locked at startup, by lock_history or after being idle
+  useEffect(() => {
+    const refresh = () => invoke<VaultStatus>("vault_status").then(setVault).catch(() => {});
+    refresh();
+    const unlistenLocked = listen("vault://locked", () => {
+      setItems([]);
+      refresh();
+      log("[vault] locked");
+    });
+    const unlistenUnlocked = listen("vault://unlocked", () => {
+      refresh();
+      log("[vault] unlocked");
+    });
+    // Typing or clicking here counts as using ClipMind
+    const touch = () => {
+      if (Date.now() - lastTouch.current < TOUCH_INTERVAL_MS) return;
+      lastTouch.current = Date.now();
+      invoke("touch_vault").catch(() => {});
+    };
+    window.addEventListener("keydown", touch);
+    window.addEventListener("mousedown", touch);
+    return () => {
+      unlistenLocked.then((f) => f());
+      unlistenUnlocked.then((f) => f());
+      window.removeEventListener("keydown", touch);
+      window.removeEventListener("mousedown", touch);
+    };
+  }, []);
+
+  const unlockHistory = async () => {
+    const passphrase = window.prompt("Passphrase to unlock ClipMind history:");
+    if (!passphrase) return;
+    try {
+      await invoke("unlock", { passphrase });
+    } catch (e) {
+      pushErr(`[vault] ${describeError(e)}`);
+    }
+  };
+
+  const encryptHistory = async () => {
+    const passphrase = window.prompt("New passphrase (8+ characters). It can't be recovered if you forget it:");
+    if (!passphrase) return;
+    if (window.prompt("Type the passphrase again:") !== passphrase) {
+      pushErr("[vault] passphrases didn't match");
+      return;
+    }
+    try {
+      log("[vault] encrypting history...");
+      await invoke("encrypt_history", { passphrase });
+      log("[vault] history encrypted");
+    } catch (e) {
+      pushErr(`[vault] ${describeError(e)}`);
+    }
+  };
+
+  useEffect(() => {
+    const unlisten = listen<ArchiveProgress>("history://progress", (e) => setArchive(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  // The format follows the extension, a .zip bundle also carries the screenshot files
+  const exportHistory = async () => {
+    const path = window.prompt("Export to (.json, .ndjson, .md or .zip):");
+    if (!path) return;
+    const format = ARCHIVE_EXTENSIONS[path.split(".").pop()?.toLowerCase() ?? ""];
+    if (!format) {
+      pushErr("[archive] use a .json, .ndjson, .md or .zip file");
+      return;
+    }
+    try {
+      const report = await invoke<{ items: number; blobs: number; missing_blobs: number }>("export_history", { format, path });
+      log(`[archive] exported ${report.items} items (${report.blobs} files, ${report.missing_blobs} missing)`);
+    } catch (e) {
+      pushErr(`[archive] ${describeError(e)}`);
+    } finally {
+      setArchive(null);
+    }
+  };
+
+  const importHistory = async () => {
+    const path = window.prompt("Import from (.json, .ndjson or .zip):");
+    if (!path) return;
+    try {
+      const report = await invoke<{ imported: number; duplicates: number; skipped: number }>("import_history", { path });
+      log(`[archive] imported ${report.imported} items (${report.duplicates} duplicates, ${report.skipped} skipped)`);
+    } catch (e) {
+      pushErr(`[archive] ${describeError(e)}`);
+    } finally {
+      setArchive(null);
+    }
+  };
+
+  // settings.toml changed, from a command or a hand edit
+  useEffect(() => {
+    const unlistenChanged = listen<SettingsChanged>("settings://changed", (e) => {
+      const { changed, restart_required } = e.payload;
+      log(`[settings] changed: ${changed.join(", ")}`);
+      if (changed.includes("autopaste")) {
+        invoke<AutoPasteStatus>("autopaste_settings").then(setAutoPaste).catch(() => {});
+      }
+      if (changed.includes("hotkeys")) {
+        invoke<HotkeyStatus[]>("list_hotkeys").then(setHotkeys).catch(() => {});
+      }
+      if (restart_required.length > 0) {
+        log(`[settings] restart to apply: ${restart_required.join(", ")}`);
+      }
+    });
+    const unlistenInvalid = listen<FieldError[]>("settings://invalid", (e) => {
+      e.payload.forEach((err) => pushErr(`[settings] ${err.field || "settings.toml"}: ${err.message}`));
+    });
+    return () => {
+      unlistenChanged.then((f) => f());
+      unlistenInvalid.then((f) => f());
+    };
+  }, []);
+
+  const toggleAutoPaste = (enabled: boolean) => {
+    invoke<AutoPasteStatus>("set_autopaste", { enabled })
+      .then(setAutoPaste)
+      .catch((e) => pushErr(`[autopaste] ${describeError(e)}`));
+  };
+
   // Check backend health
   useEffect(() => {
     const checkBackend = async () => {
+      try {
+        setProcesses(await invoke<BackendProcess[]>("backend_status", { logLines: 5 }));
+      } catch {}
+
       try {
         const res = await fetch("http://localhost:8000/");
         if (res.ok) {
@@ -184,7 +403,7 @@ export default function App() {
 
   // Fetch items when query or filter changes
   useEffect(() => {
-    if (!isQuickboard || backendStatus !== "online") return;
+    if (!isQuickboard) return;
 
     const fetchItems = async () => {
       setLoading(true);
@@ -209,7 +428,16 @@ export default function App() {
           // "all" = no filter
         }
 
-        if (q.trim()) {
+        if (q.trim() && (backendStatus !== "online" || hasFilters(q))) {
+          // Backend offline, or tag:/collection: filters only the local index knows
+          const results = await invoke<ClipItem[]>("search_items", {
+            query: q.trim(),
+            mode: filter,
+            after: afterTimestamp,
+            k: 20,
+          });
+          setItems(results);
+        } else if (q.trim()) {
           // Semantic search with mode
           const mode = filter === "all" ? "all" : filter === "text" ? "text" : filter === "images" ? "images" : "clipboard";
           const url = new URL("http://localhost:8000/search");
@@ -227,19 +455,9 @@ export default function App() {
           setItems(data.results || []);
         } else {
           // Get recent items
-          const sourceFilter = filter === "clipboard" ? "clipboard" : filter === "images" ? "screenshot" : null;
-          const url = new URL("http://localhost:8000/items/recent");
-          url.searchParams.set("limit", "20");
-          if (sourceFilter) url.searchParams.set("source", sourceFilter);
-          if (afterTimestamp) {
-            url.searchParams.set("after", afterTimestamp.toString());
-          }
-
-          console.log('[DEBUG] Fetching recent:', url.toString());
-          const res = await fetch(url.toString());
-          const data = await res.json();
-          console.log('[DEBUG] Recent items:', data);
-          setItems(data.items || []);
+          const recent = await fetchRecent(filter, afterTimestamp);
+          console.log('[DEBUG] Recent items:', recent);
+          setItems(recent);
         }
         // Reset selection to first item when results change
         setSelectedIndex(0);
@@ -257,16 +475,11 @@ export default function App() {
 
   // Auto-refresh recent items every 2 seconds when quickboard is visible
   useEffect(() => {
-    if (!isQuickboard || backendStatus !== "online") return;
+    if (!isQuickboard) return;
     if (q.trim()) return; // Don't auto-refresh during search
 
     const interval = setInterval(async () => {
       try {
-        const sourceFilter = filter === "clipboard" ? "clipboard" : filter === "images" ? "screenshot" : null;
-        const url = new URL("http://localhost:8000/items/recent");
-        url.searchParams.set("limit", "20");
-        if (sourceFilter) url.searchParams.set("source", sourceFilter);
-
         // Apply time filter
         const now = Math.floor(Date.now() / 1000);
         let afterTimestamp: number | null = null;
@@ -276,54 +489,102 @@ export default function App() {
           case "week": afterTimestamp = now - 604800; break;
           case "month": afterTimestamp = now - 2592000; break;
         }
-        if (afterTimestamp) {
-          url.searchParams.set("after", afterTimestamp.toString());
-        }
 
-        const res = await fetch(url.toString());
-        const data = await res.json();
-        setItems(data.items || []);
+        setItems(await fetchRecent(filter, afterTimestamp));
       } catch (e) {
         // Silently fail - don't disrupt user experience
       }
     }, 2000); // Refresh every 2 seconds
 
     return () => clearInterval(interval);
-  }, [isQuickboard, backendStatus, q, filter, timeRange]);
-
-  // Copy item to clipboard and close
-const handleItemClick = async (item: ClipItem) => {
-  try {
-    if (item.source === "screenshot" && item.blob_uri) {
-      // Get RGBA data with dimensions from Rust
-      const result: [number[], number, number] = await invoke('read_image_file', {
-        path: item.blob_uri
-      });
+  }, [isQuickboard, q, filter, timeRange]);
 
-      const [rgbaBytes, width, height] = result;
-      const uint8Array = new Uint8Array(rgbaBytes);
+  // Pins changed here or in another window, refresh without moving the selection
+  useEffect(() => {
+    if (!isQuickboard || q.trim()) return;
+    const unlisten = listen<number[]>("pins://changed", () => {
+      fetchRecent(filter, rangeStart(timeRange)).then(setItems).catch(() => {});
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, [isQuickboard, q, filter, timeRange]);
 
-      // writeImage needs an object with rgba, width, height
-      await writeImage({
-        rgba: uint8Array,
-        width: width,
-        height: height
-      });
+  const togglePin = async (item: ClipItem) => {
+    try {
+      await invoke(isPinned(item) ? "unpin_item" : "pin_item", { id: item.id });
+      log(`[qb] ${isPinned(item) ? "unpinned" : "pinned"} item ${item.id}`);
+    } catch (e) {
+      pushErr(`[qb] pin failed: ${describeError(e)}`);
+    }
+  };
+
+  // Pinned items sit at the top of the list, so their index is their pin position
+  const movePin = async (index: number, delta: number) => {
+    const ids = items.filter(isPinned).map((item) => item.id);
+    const target = index + delta;
+    if (index >= ids.length || target < 0 || target >= ids.length) return;
+    [ids[index], ids[target]] = [ids[target], ids[index]];
+    try {
+      await invoke("reorder_pins", { ids });
+      setSelectedIndex(target);
+    } catch (e) {
+      pushErr(`[qb] reorder failed: ${describeError(e)}`);
+    }
+  };
 
-      log(`[qb] copied image ${item.id} (${width}x${height})`);
-    } else {
-      await writeText(item.text);
-      log(`[qb] copied text ${item.id}`);
+  // Ctrl+T: tag every search result, or just the selected item when not searching
+  const tagItems = async () => {
+    const input = window.prompt(q.trim() ? "Tag all results with:" : "Tag item with:");
+    const tags = (input ?? "").split(/[\s,]+/).filter(Boolean);
+    if (tags.length === 0) return;
+    try {
+      if (q.trim()) {
+        const report = await invoke<{ matched: number; tagged: number }>("tag_search_results", {
+          query: q.trim(),
+          mode: filter,
+          after: rangeStart(timeRange),
+          tags,
+        });
+        log(`[qb] tagged ${report.matched} results with ${tags.join(", ")}`);
+      } else if (items[selectedIndex]) {
+        await invoke("tag_items", { ids: [items[selectedIndex].id], tags });
+        log(`[qb] tagged item ${items[selectedIndex].id} with ${tags.join(", ")}`);
+      }
+    } catch (e) {
+      pushErr(`[qb] tagging failed: ${describeError(e)}`);
     }
+  };
+
+  // Mark items holding secrets (or redacted ones) with a lock badge
+  const [sensitiveIds, setSensitiveIds] = useState<Set<number>>(new Set());
+  useEffect(() => {
+    if (!isQuickboard) return;
 
-    // Make sure we hide the window after copying
-    const w = await getCurrentWebviewWindow();
-    await w.hide();
-    log(`[qb] window hidden after copy`);
+    (async () => {
+      for (const item of items) {
+        if (sensitiveCache.has(item.id) || item.source !== "clipboard") continue;
+        try {
+          const scan = await invoke<ScanResult>("scan_text", { text: item.text });
+          sensitiveCache.set(item.id, scan.sensitive || item.text.includes("[redacted:"));
+        } catch {
+          sensitiveCache.set(item.id, false);
+        }
+      }
+      setSensitiveIds(new Set(items.filter((item) => sensitiveCache.get(item.id)).map((item) => item.id)));
+    })();
+  }, [isQuickboard, items]);
+
+  // Copy item to clipboard and close
+const handleItemClick = async (item: ClipItem) => {
+  try {
+    // Rust loads the item, restores its original formats and hides the quickboard
+    const copied: CopyResult = await invoke('copy_item', { id: item.id, hide: true });
+    log(`[qb] copied item ${copied.id} as ${copied.formats.join(', ')}`);
   } catch (e: any) {
     console.error("[DEBUG] Copy failed:", e);
-    log(`[qb] copy failed: ${e?.message ?? String(e)}`);
-    pushErr(`[qb] copy failed: ${e?.message ?? String(e)}`);
+    log(`[qb] copy failed: ${describeError(e)}`);
+    pushErr(`[qb] copy failed: ${describeError(e)}`);
 
     // Still try to hide the window even if copy failed
     try {
@@ -363,56 +624,11 @@ const handleItemClick = async (item: ClipItem) => {
         log(`[env] current label = ${current.label}`);
 
         if (current.label === "main") {
-          const init: Record<string, HKStatus> = {};
-          hotkeys.forEach((hk) => (init[hk] = "pending"));
-          setHkStatuses(init);
-
-          await ensureQuickboardWindow(log, pushErr);
-
-          for (const hk of hotkeys) {
-  try {
-    // FORCE unregister first, even if it fails
-    try {
-      await unregister(hk);
-      log(`[hk] force unregistered: ${hk}`);
-    } catch {
-      log(`[hk] nothing to unregister for: ${hk}`);
-    }
-
-    // Small delay to let the OS release the hotkey
-    await new Promise(resolve => setTimeout(resolve, 100));
-
-    // Now register
-    await register(hk, async () => {
-      log(`[hk] fired: ${hk}`);
-      await toggleQuickboard(log, pushErr);
-    });
-    log(`[hk] registered successfully: ${hk}`);
-    setHkStatuses((s) => ({ ...s, [hk]: "ok" }));
-  } catch (e: any) {
-    const msg = `[hk] register failed ${hk}: ${e?.message ?? String(e)}`;
-    pushErr(msg);
-    setHkStatuses((s) => ({ ...s, [hk]: "failed" }));
-  }
-}
-
-          // Keepalive: Re-register hotkeys every 30 seconds to prevent them from dying
-          const keepalive = setInterval(async () => {
-            for (const hk of hotkeys) {
-              try {
-                const registered = await isRegistered(hk);
-                if (!registered) {
-                  log(`[hk] re-registering dead hotkey: ${hk}`);
-                  await register(hk, async () => {
-                    log(`[hk] fired: ${hk}`);
-                    await toggleQuickboard(log, pushErr);
-                  });
-                }
-              } catch (e: any) {
-                pushErr(`[hk] keepalive failed: ${e?.message ?? String(e)}`);
-              }
-            }
-          }, 30000); // Every 30 seconds
+          const statuses = await invoke<HotkeyStatus[]>("list_hotkeys");
+          setHotkeys(statuses);
+          statuses
+            .filter((hk) => hk.state !== "registered")
+            .forEach((hk) => pushErr(`[hk] ${hk.shortcut} ${hk.state}: ${hk.error ?? ""}`));
 
           if (import.meta.env.DEV && !didAutoOpen.current) {
             didAutoOpen.current = true;
@@ -420,9 +636,6 @@ const handleItemClick = async (item: ClipItem) => {
               toggleQuickboard(log, pushErr);
             }, 500);
           }
-
-          // Cleanup keepalive on unmount
-          return () => clearInterval(keepalive);
         }
 
         if (current.label === "quickboard") {
@@ -432,10 +645,6 @@ const handleItemClick = async (item: ClipItem) => {
         pushErr(`[init] ${e?.message ?? String(e)}`);
       }
     })();
-
-    return () => {
-      hotkeys.forEach((hk) => unregister(hk).catch(() => {}));
-    };
   }, []);
 
   // MAIN window dashboard
@@ -467,11 +676,31 @@ const handleItemClick = async (item: ClipItem) => {
               }} />
               <span>{backendStatus === "online" ? "Connected to API" : backendStatus === "offline" ? "Offline" : "Checking..."}</span>
             </div>
-            {backendStatus === "offline" && (
-              <div style={{ marginTop: 8, fontSize: 13, opacity: 0.8 }}>
-                Start backend: <code>python -m uvicorn app.api.server:app --reload</code>
+            {processes.map((p) => (
+              <div key={p.name} style={{ marginTop: 8, fontSize: 13 }}>
+                <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+                  <span>
+                    <code>{p.name}</code>{" "}
+                    <span style={{ color: p.running ? "#6dd57e" : "#ff6b6b" }}>
+                      {p.running ? `running (pid ${p.pid})` : "stopped"}
+                    </span>
+                    {p.restarts > 0 && <span style={{ opacity: 0.7 }}> • {p.restarts} restarts</span>}
+                  </span>
+                  <button
+                    onClick={() => invoke("backend_restart", { name: p.name }).catch((e) => pushErr(`[backend] restart failed: ${e}`))}
+                    style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}
+                  >
+                    Restart
+                  </button>
+                </div>
+                {p.last_error && <div style={{ color: "#ff9b9b", marginTop: 4 }}>{p.last_error}</div>}
+                {!p.running && p.logs.length > 0 && (
+                  <div style={{ fontFamily: "ui-monospace, monospace", fontSize: 11, opacity: 0.7, whiteSpace: "pre-wrap", marginTop: 4 }}>
+                    {p.logs.map((l) => l.line).join("\n")}
+                  </div>
+                )}
               </div>
-            )}
+            ))}
             {stats && (
               <div style={{ marginTop: 12, fontSize: 13, display: "grid", gridTemplateColumns: "1fr 1fr", gap: 8 }}>
                 <div>Total Items: <strong>{stats.total_items}</strong></div>
@@ -482,18 +711,94 @@ const handleItemClick = async (item: ClipItem) => {
             )}
           </div>
 
+          <div style={{ marginTop: 12, padding: 12, background: "#1a1a1a", borderRadius: 10 }}>
+            <div style={{ fontWeight: 600, marginBottom: 8 }}>Capture</div>
+            <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+              <span style={{ color: capture.state === "recording" ? "#6dd57e" : "#f0c060" }}>
+                {capture.state === "recording"
+                  ? "Recording"
+                  : capture.resume_at
+                    ? `Paused until ${new Date(capture.resume_at * 1000).toLocaleTimeString()}`
+                    : "Paused"}
+              </span>
+              <span style={{ display: "flex", gap: 6 }}>
+                {capture.state === "recording" ? (
+                  <>
+                    <button onClick={() => setCapturePaused(15 * 60)} style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}>
+                      Pause 15 min
+                    </button>
+                    <button onClick={() => setCapturePaused(null)} style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}>
+                      Pause
+                    </button>
+                  </>
+                ) : (
+                  <button onClick={() => setCapturePaused(false)} style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}>
+                    Resume
+                  </button>
+                )}
+              </span>
+            </div>
+            {autoPaste && (
+              <label style={{ display: "flex", alignItems: "center", gap: 6, marginTop: 8, fontSize: 13, opacity: autoPaste.unavailable ? 0.5 : 1 }} title={autoPaste.unavailable ?? undefined}>
+                <input
+                  type="checkbox"
+                  checked={autoPaste.enabled}
+                  disabled={!!autoPaste.unavailable}
+                  onChange={(e) => toggleAutoPaste(e.target.checked)}
+                />
+                Paste into the previous app after picking from the quickboard
+              </label>
+            )}
+          </div>
+
+          {vault && (
+            <div style={{ marginTop: 12, padding: 12, background: "#1a1a1a", borderRadius: 10 }}>
+              <div style={{ fontWeight: 600, marginBottom: 8 }}>History Encryption</div>
+              <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+                <span style={{ color: !vault.encrypted ? "#888" : vault.locked ? "#f0c060" : "#6dd57e" }}>
+                  {!vault.encrypted
+                    ? "Not encrypted"
+                    : vault.locked
+                      ? "Locked, nothing is captured until unlocked"
+                      : vault.auto_lock_mins > 0
+                        ? `Unlocked, locks after ${vault.auto_lock_mins} min idle`
+                        : "Unlocked"}
+                </span>
+                <button
+                  onClick={() => (!vault.encrypted ? encryptHistory() : vault.locked ? unlockHistory() : invoke("lock_history").catch((e) => pushErr(`[vault] ${describeError(e)}`)))}
+                  style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}
+                >
+                  {!vault.encrypted ? "Encrypt" : vault.locked ? "Unlock" : "Lock now"}
+                </button>
+              </div>
+            </div>
+          )}
+
+          <div style={{ marginTop: 12, padding: 12, background: "#1a1a1a", borderRadius: 10 }}>
+            <div style={{ fontWeight: 600, marginBottom: 8 }}>Export / Import</div>
+            <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+              <span style={{ color: "#888" }}>
+                {archive ? `${archive.operation === "export" ? "Exporting" : "Importing"} ${archive.done} / ${archive.total}` : "JSON, NDJSON, Markdown or a zip bundle"}
+              </span>
+              <div style={{ display: "flex", gap: 6 }}>
+                <button onClick={exportHistory} disabled={archive !== null} style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}>
+                  Export
+                </button>
+                <button onClick={importHistory} disabled={archive !== null || vault?.locked} style={{ padding: "2px 10px", borderRadius: 6, background: "#333", color: "#ddd", border: "none", cursor: "pointer" }}>
+                  Import
+                </button>
+              </div>
+            </div>
+          </div>
+
           <div style={{ marginTop: 12, padding: 12, background: "#1a1a1a", borderRadius: 10 }}>
             <div style={{ fontWeight: 600, marginBottom: 8 }}>Hotkey Status</div>
             {hotkeys.map((hk) => {
-              const st = hkStatuses[hk] ?? "pending";
-              const color =
-                st === "ok" || st === "exists" ? "#6dd57e"
-                : st === "failed" ? "#ff6b6b"
-                : "#aaa";
+              const color = hk.state === "registered" ? "#6dd57e" : "#ff6b6b";
               return (
-                <div key={hk} style={{ display: "flex", justifyContent: "space-between", margin: "6px 0" }}>
-                  <code style={{ opacity: 0.9 }}>{hk}</code>
-                  <span style={{ color }}>{st}</span>
+                <div key={hk.action} style={{ display: "flex", justifyContent: "space-between", margin: "6px 0" }}>
+                  <code style={{ opacity: 0.9 }}>{hk.shortcut}</code>
+                  <span style={{ color }} title={hk.error ?? undefined}>{hk.state}</span>
                 </div>
               );
             })}
@@ -522,7 +827,9 @@ const handleItemClick = async (item: ClipItem) => {
               onClick={() => toggleQuickboard(log, pushErr)}
               style={{ padding: "10px 16px", borderRadius: 8, background: "#2b7cff", color: "#fff", border: "none", cursor: "pointer" }}
             >
-              Toggle Quickboard (Ctrl+Shift+Space)
+              Toggle Quickboard
+              {hotkeys.find((hk) => hk.action === "toggle_quickboard") &&
+                ` (${hotkeys.find((hk) => hk.action === "toggle_quickboard")!.shortcut})`}
             </button>
           </div>
         </div>
@@ -538,7 +845,7 @@ const handleItemClick = async (item: ClipItem) => {
           <span className="search-icon">🔎</span>
           <input
             className="search-input"
-            placeholder="Search clipboard & screenshots..."
+            placeholder="Search clipboard & screenshots... (tag:name, collection:name)"
             value={q}
             onChange={(e) => setQ(e.target.value)}
             onKeyDown={async (e) => {
@@ -546,12 +853,31 @@ const handleItemClick = async (item: ClipItem) => {
                 e.preventDefault();
                 const w = await getCurrentWebviewWindow();
                 await w.hide();
+              } else if ((e.ctrlKey || e.metaKey) && e.key.toLowerCase() === "t") {
+                e.preventDefault();
+                await tagItems();
+              } else if ((e.ctrlKey || e.metaKey) && e.key.toLowerCase() === "p" && items[selectedIndex]) {
+                e.preventDefault();
+                await togglePin(items[selectedIndex]);
+              } else if (e.altKey && (e.key === "ArrowUp" || e.key === "ArrowDown") && !q) {
+                e.preventDefault();
+                await movePin(selectedIndex, e.key === "ArrowUp" ? -1 : 1);
               } else if (e.key === "ArrowDown") {
                 e.preventDefault();
                 setSelectedIndex((prev) => Math.min(prev + 1, items.length - 1));
               } else if (e.key === "ArrowUp") {
                 e.preventDefault();
                 setSelectedIndex((prev) => Math.max(prev - 1, 0));
+              } else if (e.key === "Delete" && !q && items[selectedIndex]) {
+                e.preventDefault();
+                const target = items[selectedIndex];
+                try {
+                  await invoke("delete_item", { id: target.id });
+                  setItems((prev) => prev.filter((it) => it.id !== target.id));
+                  setSelectedIndex((prev) => Math.max(0, Math.min(prev, items.length - 2)));
+                } catch (err: any) {
+                  pushErr(`[qb] delete failed: ${err?.message ?? String(err)}`);
+                }
               } else if (e.key === "Enter") {
                 e.preventDefault();
                 if (items.length > 0 && items[selectedIndex]) {
@@ -597,16 +923,21 @@ const handleItemClick = async (item: ClipItem) => {
       </div>
 
       <div className="items-container">
-        {backendStatus === "offline" && (
-          <div className="no-items">Backend offline - Start with: python -m uvicorn app.api.server:app --reload</div>
+        {backendStatus === "offline" && q.trim() && (
+          <div className="no-items">Backend offline - showing keyword matches only</div>
         )}
-        {backendStatus === "online" && loading && (
+        {loading && (
           <div className="loading">Searching...</div>
         )}
-        {backendStatus === "online" && !loading && items.length === 0 && (
+        {vault?.locked && (
+          <div className="no-items">
+            History is locked <button className="filter-btn" onClick={unlockHistory}>Unlock</button>
+          </div>
+        )}
+        {!loading && !vault?.locked && items.length === 0 && (
           <div className="no-items">No items found</div>
         )}
-        {backendStatus === "online" && !loading && items.map((item, index) => (
+        {!loading && items.map((item, index) => (
           <div
             key={item.id}
             ref={(el) => (itemRefs.current[index] = el)}
@@ -615,25 +946,13 @@ const handleItemClick = async (item: ClipItem) => {
           >
             <div className="item-icon">
               {item.source === "clipboard" ? "📋" : "🖼️"}
+              {sensitiveIds.has(item.id) && <span title="May contain a secret">🔒</span>}
+              {isPinned(item) && <span title="Pinned (Ctrl+P to unpin, Alt+↑/↓ to move)">📌</span>}
             </div>
             <div className="item-content">
               {item.source === "screenshot" && item.blob_uri ? (
                 <div style={{ display: "flex", gap: "12px", alignItems: "center", width: "100%" }}>
-                  <img
-                    src={`http://localhost:8000/item/${item.id}/image`}
-                    alt="Screenshot preview"
-                    style={{
-                      width: "80px",
-                      height: "60px",
-                      objectFit: "cover",
-                      borderRadius: "4px",
-                      flexShrink: 0
-                    }}
-                    onError={(e) => {
-                      // Fallback if image fails to load
-                      e.currentTarget.style.display = "none";
-                    }}
-                  />
+                  <Thumbnail id={item.id} />
                   <div style={{ flex: 1, minWidth: 0 }}>
                     <div className="item-text">{item.preview || item.text}</div>
                     <div className="item-time">